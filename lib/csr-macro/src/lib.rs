@@ -1,8 +1,26 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Fields};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_macro_input, Attribute, Data, DataStruct, DeriveInput, Fields, GenericArgument, Ident,
+    Lit, LitInt, LitStr, PathArguments, Type,
+};
 
-#[proc_macro_derive(Csr)]
+/// Derive `Display` & `Csr` for a struct of bitfields
+///
+/// CSRs are registered by address & name with a `#[csr(...)]` attribute:
+/// - `addr = 0x300, name = "mstatus"`
+/// - `regs(NAME = ADDR, ...)` for a struct used by several CSRs, e.g.
+///   `regs(sstatus = 0x100, vsstatus = 0x200)`
+/// - `xlen = 32` to only use the struct on RV32 harts
+/// - `count = N` for `N` numbered CSRs at consecutive addresses, numbered from `first = K`
+///   (default 0), e.g. `addr = 0x3a0, name = "pmpcfg", count = 16`
+///
+/// `xlen`, `count` & `first` apply to every CSR of the attribute. Structs with entries are
+/// collected into `csr::REGISTRY`.
+///
+/// The struct is displayed by its lowercase name, unless renamed by `#[csr(rename = "NAME")]`,
+/// e.g. to display an RV32 layout by the name of its CSR
+#[proc_macro_derive(Csr, attributes(csr))]
 pub fn csr(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     impl_csr_macro(&ast)
@@ -26,21 +44,81 @@ fn impl_csr_macro(ast: &DeriveInput) -> TokenStream {
     });
 
     // Identifiers for printing
-    let name_str = format!("{}", struct_name).to_lowercase();
-    let hdiv_str = "-".repeat(name_str.len());
-    let field_fname = field_name.clone();
-    let field_fstr = field_name.clone().map(|field| format!("{}: {{}}", field));
+    let name_str = rename(&ast.attrs).unwrap_or_else(|| format!("{}", struct_name).to_lowercase());
+    let field_str = field_name.clone().map(|field| field.to_string());
+    let field_type = fields.iter().map(|field| &field.ty);
+    let field_lstr = field_str.clone();
+    let (field_kind, field_bits): (Vec<_>, Vec<_>) = field_type
+        .clone()
+        .map(|ty| {
+            let (kind, bits) = layout(ty);
+            let (start, end): (Vec<_>, Vec<_>) = bits.into_iter().unzip();
+            (kind, quote!(&[#((#start, #end)),*]))
+        })
+        .unzip();
+    let field_index = 0..fields.len();
+    let field_iname = field_name.clone();
+    let field_aname = field_name.clone();
+    let field_atype = field_type.clone();
+    let (entry_addr, entry_name, entry_xlen) = entries(&ast.attrs);
+    let entry_xlen = entry_xlen.iter().map(|xlen| match xlen {
+        Some(xlen) => quote!(Some(#xlen)),
+        None => quote!(None),
+    });
+    let register = match entry_addr.is_empty() {
+        true => quote!(),
+        false => {
+            let ident = format_ident!("REGISTER_{}", struct_name.to_string().to_uppercase());
+            quote! {
+                #[cfg(feature = "std")]
+                #[linkme::distributed_slice(crate::csr::REGISTRY)]
+                static #ident: (&[crate::csr::Entry], crate::csr::Build) =
+                    (#struct_name::CSRS, crate::csr::build::<#struct_name>);
+            }
+        }
+    };
+    let field_doc = field_name
+        .clone()
+        .map(|field| format!("Get the `{}` bitfield", field));
 
     // Generate code
     let gen = quote! {
-        impl Display for #struct_name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                writeln!(f, #name_str)?;
-                writeln!(f, #hdiv_str)?;
+        #register
+
+        impl #struct_name {
+            /// Static layout of the fields in order of declaration
+            pub const FIELDS: &'static [crate::format::FieldInfo] = &[
                 #(
-                    writeln!(f, #field_fstr, &self.#field_fname)?;
+                    crate::format::FieldInfo {
+                        name: #field_lstr,
+                        bits: #field_bits,
+                        kind: #field_kind,
+                    },
                 )*
-                Ok(())
+            ];
+
+            /// CSR addresses & names the struct is declared for
+            pub const CSRS: &'static [crate::csr::Entry] = &[
+                #(
+                    crate::csr::Entry {
+                        addr: #entry_addr,
+                        name: #entry_name,
+                        xlen: #entry_xlen,
+                    },
+                )*
+            ];
+
+            #(
+                #[doc = #field_doc]
+                pub fn #field_aname(&self) -> &#field_atype {
+                    &self.#field_aname
+                }
+            )*
+        }
+
+        impl Display for #struct_name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                crate::render::Render::Plain.write_csr(self, f)
             }
         }
 
@@ -53,10 +131,169 @@ fn impl_csr_macro(ast: &DeriveInput) -> TokenStream {
                 }
             }
 
-            fn name() -> String {
-                "#name_p".into()
+            fn name(&self) -> &'static str {
+                #name_str
+            }
+
+            #[cfg(feature = "std")]
+            fn set(&self, value: u64, field: &str, text: &str) -> Result<u64, crate::format::FieldError> {
+                match field {
+                    #(
+                        #field_str => <#field_type>::set(value, text)
+                            .ok_or_else(|| crate::format::FieldError::Value(field.into(), text.into())),
+                    )*
+                    _ => Err(crate::format::FieldError::Unknown(field.into(), #name_str.into())),
+                }
+            }
+
+            fn layout(&self) -> &'static [crate::format::FieldInfo] {
+                Self::FIELDS
+            }
+
+            fn field(&self, index: usize) -> Option<&dyn crate::bitfield::FieldValue> {
+                match index {
+                    #(
+                        #field_index => Some(&self.#field_iname),
+                    )*
+                    _ => None,
+                }
+            }
+
+            #[cfg(feature = "std")]
+            fn fields(&self) -> crate::format::Fields<'_> {
+                crate::format::Fields::new(self)
             }
         }
     };
     gen.into()
 }
+
+/// Get the decoder & bit ranges `(START, END)` of a `BitField<T, START, END>` or
+/// `MultiBitField<T, SEGMENTS>` field type
+fn layout(ty: &Type) -> (String, Vec<(&GenericArgument, &GenericArgument)>) {
+    let expected = "expected a BitField<T, START, END> or MultiBitField<T, SEGMENTS> field";
+    let (ident, args) = generics(ty).expect(expected);
+    let kind = args
+        .first()
+        .expect(expected)
+        .to_token_stream()
+        .to_string()
+        .replace(' ', "");
+    let bits = match (ident.to_string().as_str(), args.as_slice()) {
+        ("BitField", [_, start, end]) => vec![(*start, *end)],
+        ("MultiBitField", [_, GenericArgument::Type(Type::Tuple(segments))]) => {
+            segments.elems.iter().map(segment).collect()
+        }
+        ("MultiBitField", [_, GenericArgument::Type(seg)]) => vec![segment(seg)],
+        _ => panic!("{}", expected),
+    };
+    (kind, bits)
+}
+
+/// Get the bit range `(START, END)` of a `Seg<START, END, AT>` segment type
+fn segment(ty: &Type) -> (&GenericArgument, &GenericArgument) {
+    match generics(ty) {
+        Some((ident, args)) if ident == "Seg" && args.len() == 3 => (args[0], args[1]),
+        _ => panic!("expected a Seg<START, END, AT> segment"),
+    }
+}
+
+/// Get the name & generic arguments of a type
+fn generics(ty: &Type) -> Option<(&Ident, Vec<&GenericArgument>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    Some((&segment.ident, args.args.iter().collect()))
+}
+
+/// Get the display name declared by a `#[csr(rename = "NAME")]` attribute
+fn rename(attrs: &[Attribute]) -> Option<String> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("csr")) {
+        // Other keys are checked by `entries`
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("regs") {
+                return meta.parse_nested_meta(|reg| {
+                    reg.value()?.parse::<Lit>()?;
+                    Ok(())
+                });
+            }
+            let value = meta.value()?;
+            if meta.path.is_ident("rename") {
+                rename = Some(value.parse::<LitStr>()?.value());
+            } else {
+                value.parse::<Lit>()?;
+            }
+            Ok(())
+        });
+    }
+    rename
+}
+
+/// Get the addresses, names & XLEN restrictions declared by `#[csr(...)]` attributes
+fn entries(attrs: &[Attribute]) -> (Vec<u16>, Vec<String>, Vec<Option<usize>>) {
+    let mut entries = (Vec::new(), Vec::new(), Vec::new());
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("csr")) {
+        let (mut addr, mut name, mut xlen, mut count, mut first) = (None, None, None, None, 0);
+        let (mut regs, mut renamed) = (Vec::new(), false);
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("regs") {
+                return meta.parse_nested_meta(|reg| {
+                    let name = reg
+                        .path
+                        .get_ident()
+                        .ok_or_else(|| reg.error("expected a name"))?;
+                    let addr = reg.value()?.parse::<LitInt>()?.base10_parse::<u16>()?;
+                    regs.push((addr, name.to_string()));
+                    Ok(())
+                });
+            }
+            let value = meta.value()?;
+            if meta.path.is_ident("addr") {
+                addr = Some(value.parse::<LitInt>()?.base10_parse::<u16>()?);
+            } else if meta.path.is_ident("name") {
+                name = Some(value.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("xlen") {
+                xlen = Some(value.parse::<LitInt>()?.base10_parse::<usize>()?);
+            } else if meta.path.is_ident("count") {
+                count = Some(value.parse::<LitInt>()?.base10_parse::<u16>()?);
+            } else if meta.path.is_ident("first") {
+                first = value.parse::<LitInt>()?.base10_parse::<u16>()?;
+            } else if meta.path.is_ident("rename") {
+                renamed = true;
+                value.parse::<LitStr>()?;
+            } else {
+                return Err(meta.error("expected addr, name, regs, xlen, count, first or rename"));
+            }
+            Ok(())
+        })
+        .expect("expected #[csr(addr = ADDR, name = \"NAME\", ...)]");
+        match (addr, name) {
+            (Some(addr), Some(name)) => regs.insert(0, (addr, name)),
+            (None, None) if renamed || !regs.is_empty() => {}
+            (None, _) => panic!("expected an addr in #[csr(...)]"),
+            (_, None) => panic!("expected a name in #[csr(...)]"),
+        }
+        for (addr, name) in regs {
+            match count {
+                Some(count) => {
+                    for n in 0..count {
+                        entries.0.push(addr + n);
+                        entries.1.push(format!("{}{}", name, first + n));
+                        entries.2.push(xlen);
+                    }
+                }
+                None => {
+                    entries.0.push(addr);
+                    entries.1.push(name);
+                    entries.2.push(xlen);
+                }
+            }
+        }
+    }
+    entries
+}
@@ -1,50 +1,84 @@
 //! Defines a generic [BitField] struct as well as types for formatting
-use std::{fmt::Display, marker::PhantomData, mem::size_of};
+//!
+//! _Decoders write into a [StyledWrite], e.g. any [core::fmt::Write], and need neither `std` nor
+//! heap allocation_
+use core::{fmt, fmt::Display, marker::PhantomData, mem::size_of};
 
 /// Binary
 pub struct Bin;
 impl BitFieldType for Bin {
-    fn decode(value: u64, size: usize) -> String {
-        let bit_str = format!("{:b}", value);
-        let zeroes = "0".repeat(size - bit_str.len());
-        format!("0b{}{}", zeroes, bit_str)
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!("0b{:01$b}", value, size))
     }
 }
 /// Boolean
 pub struct Bool;
 impl BitFieldType for Bool {
-    fn decode(value: u64, _size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
         match value {
-            0b0 => "false".into(),
-            0b1 => "true".into(),
-            n => format!("\x1b[33mCould not represent 0b{:b} as boolean\x1b[0m", n),
+            0b0 => out.normal(&"false"),
+            0b1 => out.normal(&"true"),
+            n => out.warning(&format_args!("Could not represent 0b{:b} as boolean", n)),
         }
     }
 }
 /// Hexidecimal
 pub struct Hex;
 impl BitFieldType for Hex {
-    fn decode(value: u64, _size: usize) -> String {
-        format!("0x{:x}", value)
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!("0x{:x}", value))
     }
 }
 /// Decimal
 pub struct Dec;
 impl BitFieldType for Dec {
-    fn decode(value: u64, _size: usize) -> String {
-        format!("{}", value)
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&value)
+    }
+}
+/// Two's complement signed decimal & hexidecimal
+pub struct Signed;
+impl BitFieldType for Signed {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        let value = sext(value, size);
+        match value < 0 {
+            true => out.normal(&format_args!("{} (-0x{:x})", value, value.unsigned_abs())),
+            false => out.normal(&format_args!("{} (0x{:x})", value, value)),
+        }
+    }
+    fn encode(s: &str, size: usize) -> Option<u64> {
+        let value = parse_int(s)?;
+        let fits = size == 64 || (-(1 << (size - 1))..1 << size).contains(&value);
+        fits.then_some(value as u64 & mask(size))
+    }
+}
+/// Sign extended to 64 bits, e.g. a virtual address, along with the value in decimal if negative
+pub struct SExt;
+impl BitFieldType for SExt {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        let signed = sext(value, size);
+        out.normal(&format_args!("0x{:x} -> 0x{:x}", value, signed as u64))?;
+        match signed < 0 {
+            true => out.normal(&format_args!(" ({})", signed)),
+            false => Ok(()),
+        }
+    }
+    fn encode(s: &str, size: usize) -> Option<u64> {
+        let value = parse_uint(s)?;
+        let fits = value & !mask(size) == 0 || sext(value & mask(size), size) as u64 == value;
+        fits.then_some(value & mask(size))
     }
 }
 /// Architecture
 pub struct Arch;
 impl BitFieldType for Arch {
     /// Decode architecture
-    fn decode(value: u64, _size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
         match value {
-            0b01 => "RV32".into(),
-            0b10 => "RV64".into(),
-            0b11 => "RV128".into(),
-            n => format!("\x1b[33mInvalid architecture (0b{:b})\x1b[0m", n),
+            0b01 => out.normal(&"RV32"),
+            0b10 => out.normal(&"RV64"),
+            0b11 => out.normal(&"RV128"),
+            n => out.warning(&format_args!("Invalid architecture (0b{:b})", n)),
         }
     }
 }
@@ -52,70 +86,96 @@ impl BitFieldType for Arch {
 pub struct Priv;
 impl BitFieldType for Priv {
     /// Decode privilege level
-    fn decode(value: u64, _size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        match value {
+            0b00 => out.normal(&"User"),
+            0b01 => out.normal(&"Supervisor"),
+            0b11 => out.normal(&"Machine"),
+            n => out.warning(&format_args!("Invalid privilege (0b{:b})", n)),
+        }
+    }
+    /// Encode privilege level, also accepts `U`, `S` and `M`
+    fn encode(s: &str, _size: usize) -> Option<u64> {
+        let is = |name: &str| s.eq_ignore_ascii_case(name);
+        if is("u") || is("user") {
+            Some(0b00)
+        } else if is("s") || is("supervisor") {
+            Some(0b01)
+        } else if is("m") || is("machine") {
+            Some(0b11)
+        } else {
+            parse_uint(s)
+        }
+    }
+}
+/// Extension context status
+pub struct ExtState;
+impl BitFieldType for ExtState {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
         match value {
-            0b00 => "User".into(),
-            0b01 => "Supervisor".into(),
-            0b11 => "Machine".into(),
-            n => format!("\x1b[33mInvalid privilege (0b{:b})\x1b[0m", n),
+            0b00 => out.normal(&"Off"),
+            0b01 => out.normal(&"Initial"),
+            0b10 => out.normal(&"Clean"),
+            0b11 => out.normal(&"Dirty"),
+            n => out.warning(&format_args!("Invalid extension state (0b{:b})", n)),
         }
     }
 }
 /// Address translation & protection mode
 pub struct Atp;
 impl BitFieldType for Atp {
-    fn decode(value: u64, _size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
         match value {
-            0x0 => "Bare".into(),
-            0x1 => "Sv32".into(),
-            0x8 => "Sv39".into(),
-            0x9 => "Sv48".into(),
-            0xa => "Sv57".into(),
-            n => format!(
-                "\x1b[33mInvalid address translation mode (0b{:b})\x1b[0m",
+            0x0 => out.normal(&"Bare"),
+            0x1 => out.normal(&"Sv32"),
+            0x8 => out.normal(&"Sv39"),
+            0x9 => out.normal(&"Sv48"),
+            0xa => out.normal(&"Sv57"),
+            n => out.warning(&format_args!(
+                "Invalid address translation mode (0b{:b})",
                 n
-            ),
+            )),
         }
     }
 }
 /// Pysical page number
 pub struct Ppn<const ATP: usize>;
 impl BitFieldType for Ppn<32> {
-    fn decode(value: u64, _size: usize) -> String {
-        format!(
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!(
             "0x{:x} 0x{:x} -> 0x{:x}",
             get_bits(value, 10, 21),
             get_bits(value, 0, 9),
             value << 12
-        )
+        ))
     }
 }
 impl BitFieldType for Ppn<39> {
-    fn decode(value: u64, _size: usize) -> String {
-        format!(
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!(
             "0x{:x} 0x{:x} 0x{:x} -> 0x{:x}",
             get_bits(value, 18, 43),
             get_bits(value, 9, 17),
             get_bits(value, 0, 8),
             value << 12
-        )
+        ))
     }
 }
 impl BitFieldType for Ppn<48> {
-    fn decode(value: u64, _size: usize) -> String {
-        format!(
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!(
             "0x{:x} 0x{:x} 0x{:x} 0x{:x} -> 0x{:x}",
             get_bits(value, 27, 43),
             get_bits(value, 18, 26),
             get_bits(value, 9, 17),
             get_bits(value, 0, 8),
             value << 12
-        )
+        ))
     }
 }
 impl BitFieldType for Ppn<57> {
-    fn decode(value: u64, _size: usize) -> String {
-        format!(
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!(
             "0x{:x} 0x{:x} 0x{:x} 0x{:x} 0x{:x} -> 0x{:x}",
             get_bits(value, 36, 43),
             get_bits(value, 27, 35),
@@ -123,67 +183,68 @@ impl BitFieldType for Ppn<57> {
             get_bits(value, 9, 17),
             get_bits(value, 0, 8),
             value << 12
-        )
+        ))
     }
 }
 /// Trap vector mode
 pub struct Tvec;
 impl BitFieldType for Tvec {
     /// Decode xtvec mode field
-    fn decode(value: u64, _size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
         match value {
-            0x0 => "Direct".into(),
-            0x1 => "Vectored".into(),
-            n => format!("\x1b[33mInvalid (0b{:b})\x1b[0m", n),
+            0x0 => out.normal(&"Direct"),
+            0x1 => out.normal(&"Vectored"),
+            n => out.warning(&format_args!("Invalid (0b{:b})", n)),
         }
     }
 }
-/// Trap cause exception code
+/// Trap cause exception code, the interrupt flag is the most significant bit of the field
 pub struct ExcCode;
 impl BitFieldType for ExcCode {
-    fn decode(value: u64, _size: usize) -> String {
-        let interrupt = get_bit(value, 63);
-        let code = get_bits(value, 0, 62);
-        match (interrupt, code) {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        let interrupt = get_bit(value, size - 1);
+        let code = get_bits(value, 0, size - 2);
+        let text = match (interrupt, code) {
             // Interrupts
-            (0b1, 1) => "Supervisor software interrupt".into(),
-            (0b1, 2) => "Virtual supervisor software interrupt".into(),
-            (0b1, 3) => "Machine software interrupt".into(),
-            (0b1, 5) => "Supervisor timer interrupt".into(),
-            (0b1, 6) => "Virtual supervisor timer interrupt".into(),
-            (0b1, 7) => "Machine timer interrupt".into(),
-            (0b1, 9) => "Supervisor external interrupt".into(),
-            (0b1, 10) => "Virtual supervisor external interrupt".into(),
-            (0b1, 11) => "Machine external interrupt".into(),
-            (0b1, 12) => "Supervisor guest external interrrupt".into(),
+            (0b1, 1) => "Supervisor software interrupt",
+            (0b1, 2) => "Virtual supervisor software interrupt",
+            (0b1, 3) => "Machine software interrupt",
+            (0b1, 5) => "Supervisor timer interrupt",
+            (0b1, 6) => "Virtual supervisor timer interrupt",
+            (0b1, 7) => "Machine timer interrupt",
+            (0b1, 9) => "Supervisor external interrupt",
+            (0b1, 10) => "Virtual supervisor external interrupt",
+            (0b1, 11) => "Machine external interrupt",
+            (0b1, 12) => "Supervisor guest external interrrupt",
             // Synchronous exceptions
-            (0b0, 0) => "Instruction address misaligned".into(),
-            (0b0, 1) => "Instruction access fault".into(),
-            (0b0, 2) => "Illegal instruction".into(),
-            (0b0, 3) => "Breakpoint".into(),
-            (0b0, 4) => "Load address misaligned".into(),
-            (0b0, 5) => "Load access fault".into(),
-            (0b0, 6) => "Store/AMO address misaligned".into(),
-            (0b0, 7) => "Store/AMO access fault".into(),
-            (0b0, 8) => "Environment call from U-mode".into(),
-            (0b0, 9) => "Environment call from HS-mode".into(),
-            (0b0, 10) => "Environment call from VS-mode".into(),
-            (0b0, 11) => "Environment call from M-mode".into(),
-            (0b0, 12) => "Instruction page fault".into(),
-            (0b0, 13) => "Load page fault".into(),
-            (0b0, 15) => "Store/AMO page fault".into(),
-            (0b0, 20) => "Instruction guest-page fault".into(),
-            (0b0, 21) => "Load guest-page fault".into(),
-            (0b0, 22) => "Virtual instruction".into(),
-            (0b0, 23) => "Store/AMO guest-page fault".into(),
-            _ => format!("\x1b[33mUnknown exception code ({})\x1b[0m", code),
-        }
+            (0b0, 0) => "Instruction address misaligned",
+            (0b0, 1) => "Instruction access fault",
+            (0b0, 2) => "Illegal instruction",
+            (0b0, 3) => "Breakpoint",
+            (0b0, 4) => "Load address misaligned",
+            (0b0, 5) => "Load access fault",
+            (0b0, 6) => "Store/AMO address misaligned",
+            (0b0, 7) => "Store/AMO access fault",
+            (0b0, 8) => "Environment call from U-mode",
+            (0b0, 9) => "Environment call from HS-mode",
+            (0b0, 10) => "Environment call from VS-mode",
+            (0b0, 11) => "Environment call from M-mode",
+            (0b0, 12) => "Instruction page fault",
+            (0b0, 13) => "Load page fault",
+            (0b0, 15) => "Store/AMO page fault",
+            (0b0, 20) => "Instruction guest-page fault",
+            (0b0, 21) => "Load guest-page fault",
+            (0b0, 22) => "Virtual instruction",
+            (0b0, 23) => "Store/AMO guest-page fault",
+            _ => return out.warning(&format_args!("Unknown exception code ({})", code)),
+        };
+        out.normal(&text)
     }
 }
 /// PMP configuration byte
 pub struct PmpXCfg;
 impl BitFieldType for PmpXCfg {
-    fn decode(value: u64, _size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
         // Get fields
         let r: bool = (value & 0b0000_0001) != 0;
         let w: bool = (value & 0b0000_0010) != 0;
@@ -196,18 +257,52 @@ impl BitFieldType for PmpXCfg {
             0b11 => "NAPOT",
             _ => unreachable!(), // Should never occur given the mask above
         };
-        // Build formatted string
-        let mut s = String::new();
-        s += if r { "R" } else { "\x1b[90mR\x1b[0m" };
-        s += if w { "W" } else { "\x1b[90mW\x1b[0m" };
-        s += if x { "X" } else { "\x1b[90mX\x1b[0m" };
-        let mut s = format!("{} {} ", s, a);
-        s += if l { "Locked" } else { "" };
-        // Return error string if invalid permission bit combination
-        match (r, w, x) {
-            (false, true, _) => format!("\x1b[33mIllegal PMP permissions:\x1b[0m {}", s),
-            (_, _, _) => s,
+        // Write formatted value, cleared permission bits are shown as a dimmed `-`
+        if let (false, true, _) = (r, w, x) {
+            out.warning(&"Illegal PMP permissions: ")?;
+        }
+        for (set, name) in [(r, "R"), (w, "W"), (x, "X")] {
+            match set {
+                true => out.normal(&name)?,
+                false => out.write_styled(Style::Dim, &"-")?,
+            }
+        }
+        out.normal(&format_args!(" {} ", a))?;
+        if l {
+            out.normal(&"Locked")?;
+        }
+        Ok(())
+    }
+    fn encode(s: &str, _size: usize) -> Option<u64> {
+        if let Some(value) = parse_uint(s) {
+            return Some(value);
         }
+        let s = s.strip_prefix("Illegal PMP permissions:").unwrap_or(s);
+        let mut words = s.split_whitespace();
+        // Permissions as `R`, `W` & `X`, or `-` when cleared
+        let permissions = words.next()?.as_bytes();
+        if permissions.len() != 3 {
+            return None;
+        }
+        let mut value = 0;
+        for (bit, (c, name)) in permissions.iter().zip(b"RWX").enumerate() {
+            match c.to_ascii_uppercase() {
+                b'-' => {}
+                c if c == *name => value |= 1 << bit,
+                _ => return None,
+            }
+        }
+        let a = words.next()?;
+        let a = ["OFF", "TOR", "NA4", "NAPOT"]
+            .iter()
+            .position(|mode| mode.eq_ignore_ascii_case(a))?;
+        value |= (a as u64) << 3;
+        match words.next() {
+            Some(l) if l.eq_ignore_ascii_case("Locked") => value |= 0b1000_0000,
+            Some(_) => return None,
+            None => {}
+        }
+        words.next().is_none().then_some(value)
     }
 }
 /// Reserved with some value
@@ -216,13 +311,19 @@ impl<const V: u64, T> BitFieldType for Reserved<V, T>
 where
     T: BitFieldType,
 {
-    fn decode(value: u64, size: usize) -> String {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
         if value != V {
-            format!("\x1b[33mInvalid ({})\x1b[0m", T::decode(value, size))
+            let mut out = Restyle(out, Style::Warning);
+            out.normal(&"Invalid (")?;
+            T::write(value, size, &mut out)?;
+            out.normal(&")")
         } else {
-            T::decode(value, size)
+            T::write(value, size, out)
         }
     }
+    fn encode(s: &str, size: usize) -> Option<u64> {
+        T::encode(s, size)
+    }
 }
 /// Value shifted right by N bits
 ///
@@ -232,18 +333,278 @@ impl<const N: usize, T> BitFieldType for RSh<N, T>
 where
     T: BitFieldType,
 {
-    fn decode(value: u64, size: usize) -> String {
-        format!(
-            "{} -> {}",
-            T::decode(value, size),
-            T::decode(value << N, size)
-        )
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        T::write(value, size, out)?;
+        out.normal(&" -> ")?;
+        T::write(value << N, size, out)
+    }
+    fn encode(s: &str, size: usize) -> Option<u64> {
+        T::encode(s, size)
+    }
+}
+
+/// Integer register ABI names
+pub const XREG: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// Integer register
+pub struct XReg;
+impl BitFieldType for XReg {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!("x{} ({})", value, XREG[value as usize]))
+    }
+}
+/// Compressed integer register, encoding `x8`-`x15`
+pub struct XRegC;
+impl BitFieldType for XRegC {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        XReg::write(value + 8, size, out)
+    }
+}
+
+/// Floating point register ABI names
+pub const FREG: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+/// Floating point register
+pub struct FReg;
+impl BitFieldType for FReg {
+    fn write<W: StyledWrite + ?Sized>(value: u64, _size: usize, out: &mut W) -> fmt::Result {
+        out.normal(&format_args!("f{} ({})", value, FREG[value as usize]))
+    }
+}
+/// Compressed floating point register, encoding `f8`-`f15`
+pub struct FRegC;
+impl BitFieldType for FRegC {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        FReg::write(value + 8, size, out)
+    }
+}
+/// Major opcode
+pub struct Opcode;
+impl BitFieldType for Opcode {
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result {
+        let name = match get_bits(value, 2, 6) {
+            0x00 => "LOAD",
+            0x01 => "LOAD-FP",
+            0x02 => "custom-0",
+            0x03 => "MISC-MEM",
+            0x04 => "OP-IMM",
+            0x05 => "AUIPC",
+            0x06 => "OP-IMM-32",
+            0x08 => "STORE",
+            0x09 => "STORE-FP",
+            0x0a => "custom-1",
+            0x0b => "AMO",
+            0x0c => "OP",
+            0x0d => "LUI",
+            0x0e => "OP-32",
+            0x10 => "MADD",
+            0x11 => "MSUB",
+            0x12 => "NMSUB",
+            0x13 => "NMADD",
+            0x14 => "OP-FP",
+            0x15 => "OP-V",
+            0x16 => "custom-2",
+            0x18 => "BRANCH",
+            0x19 => "JALR",
+            0x1b => "JAL",
+            0x1c => "SYSTEM",
+            0x1e => "custom-3",
+            n => return out.warning(&format_args!("Reserved opcode (0b{:05b})", n)),
+        };
+        Bin::write(value, size, out)?;
+        out.normal(&format_args!(" ({})", name))
+    }
+}
+/// How a part of a decoded value stands out
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Style {
+    /// Regular text
+    Normal,
+    /// Invalid or unexpected value
+    Warning,
+    /// Cleared flag, e.g. a PMP permission bit
+    Dim,
+}
+
+/// A sink for styled text that decoders write into
+///
+/// _Implemented for any [fmt::Write], which drops the styles_
+pub trait StyledWrite {
+    /// Write text with a style
+    fn write_styled(&mut self, style: Style, text: &dyn Display) -> fmt::Result;
+
+    /// Write regular text
+    fn normal(&mut self, text: &dyn Display) -> fmt::Result {
+        self.write_styled(Style::Normal, text)
+    }
+
+    /// Write a warning
+    fn warning(&mut self, text: &dyn Display) -> fmt::Result {
+        self.write_styled(Style::Warning, text)
+    }
+}
+
+impl<W: fmt::Write + ?Sized> StyledWrite for W {
+    fn write_styled(&mut self, _style: Style, text: &dyn Display) -> fmt::Result {
+        write!(self, "{}", text)
+    }
+}
+
+/// Writes all text with the same style, e.g. a decoded value within a warning
+struct Restyle<'a, W: ?Sized>(&'a mut W, Style);
+
+impl<W: StyledWrite + ?Sized> StyledWrite for Restyle<'_, W> {
+    fn write_styled(&mut self, _style: Style, text: &dyn Display) -> fmt::Result {
+        self.0.write_styled(self.1, text)
+    }
+}
+
+/// Checks whether the written text equals a string, ignoring ASCII case
+struct Matches<'a>(Option<&'a str>);
+
+impl fmt::Write for Matches<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = self.0.and_then(|rest| {
+            let head = rest.get(..s.len())?;
+            head.eq_ignore_ascii_case(s).then(|| &rest[s.len()..])
+        });
+        Ok(())
+    }
+}
+
+/// A part of a decoded value's text
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// A decoded value: its raw value, where it was taken from and its text split into styled parts
+///
+/// _Styling is left to a [renderer](crate::render), [Display] gives the plain text_
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Decoded {
+    /// Raw value
+    pub value: u64,
+    /// Bit ranges `(START, END)` of a bitfield's value, empty for values that aren't bitfields
+    pub bits: Vec<(usize, usize)>,
+    /// Text
+    pub spans: Vec<Span>,
+}
+
+#[cfg(feature = "std")]
+impl Decoded {
+    /// Create a decoded value that is a warning as a whole
+    pub fn warning(text: impl Into<String>) -> Self {
+        let mut decoded = Decoded::default();
+        decoded.push(text, Style::Warning);
+        decoded
+    }
+
+    /// Append text with a style
+    pub fn push(&mut self, text: impl Into<String>, style: Style) {
+        self.spans.push(Span {
+            text: text.into(),
+            style,
+        });
+    }
+
+    /// Append the parts of another decoded value
+    pub fn append(&mut self, other: Decoded) {
+        self.spans.extend(other.spans);
+    }
+
+    /// Check whether any part is a warning
+    pub fn is_warning(&self) -> bool {
+        self.spans.iter().any(|s| s.style == Style::Warning)
+    }
+
+    /// Get the text of the parts that are warnings
+    pub fn warnings(&self) -> String {
+        self.spans
+            .iter()
+            .filter(|s| s.style == Style::Warning)
+            .map(|s| s.text.as_str())
+            .collect()
+    }
+}
+
+/// Appends text, joining it with the last part if that has the same style
+#[cfg(feature = "std")]
+impl StyledWrite for Decoded {
+    fn write_styled(&mut self, style: Style, text: &dyn Display) -> fmt::Result {
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text += &text.to_string(),
+            _ => self.push(text.to_string(), style),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<String> for Decoded {
+    fn from(text: String) -> Self {
+        let mut decoded = Decoded::default();
+        decoded.push(text, Style::Normal);
+        decoded
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&str> for Decoded {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.spans.iter().try_for_each(|s| f.write_str(&s.text))
     }
 }
 
 /// Bitfield types only differ in the format they are printed
-trait BitFieldType {
-    fn decode(value: u64, size: usize) -> String;
+pub trait BitFieldType {
+    /// Write a value of `size` bits decoded
+    fn write<W: StyledWrite + ?Sized>(value: u64, size: usize, out: &mut W) -> fmt::Result;
+
+    /// Decode a value of `size` bits
+    #[cfg(feature = "std")]
+    fn decode(value: u64, size: usize) -> Decoded {
+        let mut decoded = Decoded {
+            value,
+            ..Default::default()
+        };
+        Self::write(value, size, &mut decoded).expect("Decoding into memory should not fail");
+        decoded
+    }
+
+    /// Parse a value, written as a number or as printed by [write](BitFieldType::write)
+    ///
+    /// _Printed values are only looked up for fields of up to 8 bits_
+    fn encode(s: &str, size: usize) -> Option<u64> {
+        if let Some(value) = parse_uint(s) {
+            return Some(value);
+        }
+        if size > 8 {
+            return None;
+        }
+        (0..1 << size).find(|v| {
+            let mut matches = Matches(Some(s));
+            Self::write(*v, size, &mut matches).is_ok() && matches.0 == Some("")
+        })
+    }
 }
 
 /// A bitfield with 2 constant generics to indicate the position of it's first and last bit
@@ -290,6 +651,31 @@ impl<T, const S: usize, const E: usize> BitField<T, S, E> {
     }
 }
 
+impl<T: BitFieldType, const S: usize, const E: usize> BitField<T, S, E> {
+    /// Write the bitfield's decoded value
+    pub fn write<W: StyledWrite + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        T::write(self.value(), Self::size(), out)
+    }
+
+    /// Decode the bitfield's value
+    #[cfg(feature = "std")]
+    pub fn decode(&self) -> Decoded {
+        Decoded {
+            bits: vec![(S, E)],
+            ..T::decode(self.value(), Self::size())
+        }
+    }
+
+    /// Set the bitfield's bits of raw value `raw` to the value parsed from `s`
+    ///
+    /// Returns `None` if `s` is not a valid value or does not fit in bitfield's size
+    pub fn set(raw: u64, s: &str) -> Option<u64> {
+        let value = T::encode(s, Self::size())?;
+        let fits = value & !mask(Self::size()) == 0;
+        fits.then(|| (raw & !(mask(Self::size()) << S)) | value << S)
+    }
+}
+
 impl<I, T, const S: usize, const E: usize> From<I> for BitField<T, S, E>
 where
     I: Into<u64>,
@@ -301,16 +687,209 @@ where
 }
 
 impl<T: BitFieldType, const S: usize, const E: usize> Display for BitField<T, S, E> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", T::decode(self.value(), Self::size()))
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+impl<T: BitFieldType, const S: usize, const E: usize> FieldValue for BitField<T, S, E> {
+    fn value(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&self, out: &mut dyn StyledWrite) -> fmt::Result {
+        self.write(out)
+    }
+
+    #[cfg(feature = "std")]
+    fn decode(&self) -> Decoded {
+        self.decode()
+    }
+}
+
+/// A bit segment with 3 constant generics to indicate the position of it's first and last bit
+/// and the position it is moved to within the gathered value
+///
+/// Requires `START <= END`
+pub struct Seg<const START: usize, const END: usize, const AT: usize>;
+
+/// One or more bit segments that together make up a field
+///
+/// _Implemented for [Seg] and tuples of up to 8 segments_
+pub trait Segments {
+    /// Gather the segments' bits from a raw value
+    fn gather(value: u64) -> u64;
+
+    /// Scatter a gathered value back over the segments, inverse of [gather](Segments::gather)
+    fn scatter(value: u64) -> u64;
+
+    /// Size of the gathered value in bits
+    fn size() -> usize;
+
+    /// Append the segments' bit ranges `(START, END)`
+    #[cfg(feature = "std")]
+    fn bits(bits: &mut Vec<(usize, usize)>);
+}
+
+impl<const S: usize, const E: usize, const A: usize> Segments for Seg<S, E, A> {
+    fn gather(value: u64) -> u64 {
+        assert!(S <= E);
+        get_bits(value, S, E) << A
+    }
+
+    fn scatter(value: u64) -> u64 {
+        (value >> A & mask(E - S + 1)) << S
+    }
+
+    fn size() -> usize {
+        A + E - S + 1
+    }
+
+    #[cfg(feature = "std")]
+    fn bits(bits: &mut Vec<(usize, usize)>) {
+        bits.push((S, E));
+    }
+}
+
+macro_rules! impl_segments_for_tuple {
+    ($($seg:ident),+) => {
+        impl<$($seg: Segments),+> Segments for ($($seg,)+) {
+            fn gather(value: u64) -> u64 {
+                0 $(| $seg::gather(value))+
+            }
+
+            fn scatter(value: u64) -> u64 {
+                0 $(| $seg::scatter(value))+
+            }
+
+            fn size() -> usize {
+                0 $(.max($seg::size()))+
+            }
+
+            #[cfg(feature = "std")]
+            fn bits(bits: &mut Vec<(usize, usize)>) {
+                $($seg::bits(bits);)+
+            }
+        }
+    };
+}
+impl_segments_for_tuple!(A, B);
+impl_segments_for_tuple!(A, B, C);
+impl_segments_for_tuple!(A, B, C, D);
+impl_segments_for_tuple!(A, B, C, D, E);
+impl_segments_for_tuple!(A, B, C, D, E, F);
+impl_segments_for_tuple!(A, B, C, D, E, F, G);
+impl_segments_for_tuple!(A, B, C, D, E, F, G, H);
+
+/// A bitfield whose bits are scattered over several [Seg]ments of the raw value
+///
+/// E.g. a B-type immediate: `MultiBitField<Hex, (Seg<8, 11, 1>, Seg<25, 30, 5>, Seg<7, 7, 11>, Seg<31, 31, 12>)>`
+pub struct MultiBitField<T, S>(u64, PhantomData<(T, S)>);
+
+impl<T, S: Segments> MultiBitField<T, S> {
+    /// Create a new bitfield by gathering its segments from a raw value
+    pub fn new<I>(value: I) -> Self
+    where
+        I: Into<u64>,
+    {
+        MultiBitField(S::gather(value.into()), PhantomData)
+    }
+
+    /// Get bitfield's size in bits
+    pub fn size() -> usize {
+        S::size()
+    }
+
+    /// Get bitfield's value
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl<T: BitFieldType, S: Segments> MultiBitField<T, S> {
+    /// Write the bitfield's decoded value
+    pub fn write<W: StyledWrite + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        T::write(self.value(), Self::size(), out)
+    }
+
+    /// Decode the bitfield's value
+    #[cfg(feature = "std")]
+    pub fn decode(&self) -> Decoded {
+        let mut bits = Vec::new();
+        S::bits(&mut bits);
+        Decoded {
+            bits,
+            ..T::decode(self.value(), Self::size())
+        }
+    }
+
+    /// Set the bitfield's bits of raw value `raw` to the value parsed from `s`
+    ///
+    /// Returns `None` if `s` is not a valid value or does not fit in the segments
+    pub fn set(raw: u64, s: &str) -> Option<u64> {
+        let value = T::encode(s, Self::size())?;
+        let fits = S::gather(S::scatter(value)) == value;
+        fits.then(|| (raw & !S::scatter(u64::MAX)) | S::scatter(value))
+    }
+}
+
+impl<I, T, S: Segments> From<I> for MultiBitField<T, S>
+where
+    I: Into<u64>,
+{
+    /// Converts any integer value into a bitfield
+    fn from(value: I) -> Self {
+        MultiBitField::new(value)
+    }
+}
+
+impl<T: BitFieldType, S: Segments> Display for MultiBitField<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+impl<T: BitFieldType, S: Segments> FieldValue for MultiBitField<T, S> {
+    fn value(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&self, out: &mut dyn StyledWrite) -> fmt::Result {
+        self.write(out)
+    }
+
+    #[cfg(feature = "std")]
+    fn decode(&self) -> Decoded {
+        self.decode()
+    }
+}
+
+/// A [BitField] or [MultiBitField] regardless of its type and position
+pub trait FieldValue {
+    /// Get the field's value
+    fn value(&self) -> u64;
+
+    /// Write the field's decoded value
+    fn write(&self, out: &mut dyn StyledWrite) -> fmt::Result;
+
+    /// Decode the field's value
+    #[cfg(feature = "std")]
+    fn decode(&self) -> Decoded {
+        let mut decoded = Decoded {
+            value: self.value(),
+            ..Default::default()
+        };
+        self.write(&mut decoded)
+            .expect("Decoding into memory should not fail");
+        decoded
     }
 }
 
 /// Get the bit at a specified index
-fn get_bit<I>(value: I, index: usize) -> I
+pub(crate) fn get_bit<I>(value: I, index: usize) -> I
 where
-    I: std::ops::Shl<usize, Output = I>,
-    I: std::ops::Shr<usize, Output = I>,
+    I: core::ops::Shl<usize, Output = I>,
+    I: core::ops::Shr<usize, Output = I>,
 {
     let lastbit = size_of::<I>() * 8 - 1;
     assert!(lastbit >= index);
@@ -320,10 +899,10 @@ where
 }
 
 /// Get the bits between 2 specified indices
-fn get_bits<I>(value: I, start: usize, end: usize) -> I
+pub(crate) fn get_bits<I>(value: I, start: usize, end: usize) -> I
 where
-    I: std::ops::Shl<usize, Output = I>,
-    I: std::ops::Shr<usize, Output = I>,
+    I: core::ops::Shl<usize, Output = I>,
+    I: core::ops::Shr<usize, Output = I>,
 {
     let lastbit = size_of::<I>() * 8 - 1;
     assert!(lastbit >= end);
@@ -333,6 +912,38 @@ where
     value >> (lastbit - end + start)
 }
 
+/// Get a mask of the lowest `size` bits
+pub(crate) fn mask(size: usize) -> u64 {
+    u64::MAX >> (64 - size)
+}
+
+/// Parse a decimal, `0x` hexadecimal or `0b` binary integer
+pub(crate) fn parse_uint(s: &str) -> Option<u64> {
+    if let Some(hex) = s.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = s.strip_prefix("0b") {
+        u64::from_str_radix(bin, 2).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// Parse a decimal, `0x` hexadecimal or `0b` binary integer with optional minus sign
+pub(crate) fn parse_int(s: &str) -> Option<i64> {
+    match s.strip_prefix('-') {
+        Some(s) => 0_i64.checked_sub_unsigned(parse_uint(s)?),
+        None => parse_uint(s)?.try_into().ok(),
+    }
+}
+
+/// Sign extend the lowest `size` bits of a value
+pub(crate) fn sext(value: u64, size: usize) -> i64 {
+    assert!((1..=64).contains(&size));
+
+    let shift = 64 - size;
+    ((value as i64) << shift) >> shift
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -340,7 +951,7 @@ mod test {
     #[test]
     #[should_panic]
     fn bitfield_start_after_end_panics() {
-        let _: BitField<Bin, 7, 4> = BitField::new(3 as u64);
+        let _: BitField<Bin, 7, 4> = BitField::new(3_u64);
     }
 
     #[test]
@@ -358,10 +969,108 @@ mod test {
 
     #[test]
     fn bitfield_format_bin() {
-        let b = BitField::<Bin, 0, 5>::new(0x5 as u64);
+        let b = BitField::<Bin, 0, 5>::new(0x5_u64);
         let s = format!("{}", b);
         assert_eq!(s, "0b000101");
     }
+    #[test]
+    fn multibitfield_gather() {
+        type Imm = MultiBitField<
+            Hex,
+            (
+                Seg<8, 11, 1>,
+                Seg<25, 30, 5>,
+                Seg<7, 7, 11>,
+                Seg<31, 31, 12>,
+            ),
+        >;
+        let b = Imm::new(0xfeb50ee3_u64);
+        assert_eq!(Imm::size(), 13);
+        assert_eq!(b.value(), 0x1ffc);
+    }
+
+    #[test]
+    fn multibitfield_format_bin() {
+        let b = MultiBitField::<Bin, (Seg<0, 1, 2>, Seg<4, 5, 0>)>::new(0b11_0001_u64);
+        let s = format!("{}", b);
+        assert_eq!(s, "0b0111");
+    }
+
+    #[test]
+    fn bitfield_format_signed() {
+        let b = BitField::<Signed, 20, 31>::new(0xffc58513_u64);
+        assert_eq!(format!("{}", b), "-4 (-0x4)");
+        let b = BitField::<Signed, 20, 31>::new(0x7ff58513_u64);
+        assert_eq!(format!("{}", b), "2047 (0x7ff)");
+    }
+
+    #[test]
+    fn bitfield_format_sext() {
+        let b = BitField::<SExt, 0, 38>::new(0x7f_ffff_f000_u64);
+        assert_eq!(
+            format!("{}", b),
+            "0x7ffffff000 -> 0xfffffffffffff000 (-4096)"
+        );
+        let b = BitField::<SExt, 0, 38>::new(0x3f_ffff_f000_u64);
+        assert_eq!(format!("{}", b), "0x3ffffff000 -> 0x3ffffff000");
+    }
+
+    #[test]
+    fn decode_value_and_bits() {
+        let decoded = BitField::<Bin, 4, 7>::new(0xa5_u64).decode();
+        assert_eq!(decoded.value, 0xa);
+        assert_eq!(decoded.bits, [(4, 7)]);
+        assert_eq!(decoded.to_string(), "0b1010");
+        let imm = MultiBitField::<Signed, (Seg<7, 11, 0>, Seg<25, 31, 5>)>::new(0xfe113c23_u64);
+        let decoded = imm.decode();
+        assert_eq!(decoded.value, 0xff8);
+        assert_eq!(decoded.bits, [(7, 11), (25, 31)]);
+        assert_eq!(decoded.to_string(), "-8 (-0x8)");
+    }
+
+    #[test]
+    fn bitfield_set() {
+        assert_eq!(BitField::<Priv, 11, 12>::set(0x8, "M"), Some(0x1808));
+        assert_eq!(
+            BitField::<ExtState, 13, 14>::set(0x0, "dirty"),
+            Some(0x6000)
+        );
+        assert_eq!(BitField::<Bin, 3, 3>::set(0x8, "0"), Some(0x0));
+        assert_eq!(BitField::<Bin, 3, 3>::set(0x0, "2"), None);
+        assert_eq!(
+            BitField::<Signed, 20, 31>::set(0x513, "-4"),
+            Some(0xffc00513)
+        );
+    }
+
+    #[test]
+    fn pmpxcfg_round_trip() {
+        // Bits 5 & 6 are reserved & not printed
+        for value in (0..=0xff).filter(|v| v & 0b0110_0000 == 0) {
+            let text = PmpXCfg::decode(value, 8).to_string();
+            assert_eq!(PmpXCfg::encode(&text, 8), Some(value), "{}", text);
+        }
+        assert_eq!(PmpXCfg::encode("r-x tor", 8), Some(0x0d));
+        assert_eq!(PmpXCfg::encode("RWX NAPOT Locked", 8), Some(0x9f));
+        assert_eq!(PmpXCfg::encode("RW NAPOT", 8), None);
+        assert_eq!(PmpXCfg::encode("RWX NAPOT 1", 8), None);
+    }
+
+    #[test]
+    fn multibitfield_set() {
+        type Imm = MultiBitField<
+            Hex,
+            (
+                Seg<8, 11, 1>,
+                Seg<25, 30, 5>,
+                Seg<7, 7, 11>,
+                Seg<31, 31, 12>,
+            ),
+        >;
+        assert_eq!(Imm::set(0x00b50063, "0x1ffc"), Some(0xfeb50ee3));
+        assert_eq!(Imm::set(0x00b50063, "0x1"), None);
+    }
+
     #[test]
     fn get_bit_u8() {
         assert_eq!(1, get_bit(0b0000_0010_u8, 1));
@@ -1,13 +1,14 @@
 //! Definitions for the hypervisor extended supervisor mode CSRs
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
 use super::Csr;
 use crate::bitfield::BitField;
-use crate::bitfield::{Arch, Atp, Bin, Hex, Reserved};
+use crate::bitfield::{Arch, Atp, Bin, Hex, Reserved, Signed};
 
 /// Hypervisor Status Register
 #[derive(Csr)]
+#[csr(addr = 0x600, name = "hstatus")]
 pub struct Hstatus {
     vsbe: BitField<Bin, 5, 5>,
     gva: BitField<Bin, 6, 6>,
@@ -21,8 +22,25 @@ pub struct Hstatus {
     vsxl: BitField<Arch, 32, 33>,
 }
 
+/// RV32 Hypervisor Status Register
+#[derive(Csr)]
+#[csr(rename = "hstatus")]
+#[csr(addr = 0x600, name = "hstatus", xlen = 32)]
+pub struct Hstatus32 {
+    vsbe: BitField<Bin, 5, 5>,
+    gva: BitField<Bin, 6, 6>,
+    spv: BitField<Bin, 7, 7>,
+    spvp: BitField<Bin, 8, 8>,
+    hu: BitField<Bin, 9, 9>,
+    vgein: BitField<Bin, 12, 17>,
+    vtvm: BitField<Bin, 20, 20>,
+    vtw: BitField<Bin, 21, 21>,
+    vtsr: BitField<Bin, 22, 22>,
+}
+
 /// Hypervisor Exception Delegation Register
 #[derive(Csr)]
+#[csr(addr = 0x602, name = "hedeleg")]
 pub struct Hedeleg {
     misaligned_fetch: BitField<Bin, 0x0, 0x0>,
     fetch_access: BitField<Bin, 0x1, 0x1>,
@@ -47,6 +65,7 @@ pub struct Hedeleg {
 
 /// Hypervisor Interrupt Delegation Register
 #[derive(Csr)]
+#[csr(addr = 0x603, name = "hideleg")]
 pub struct Hideleg {
     supervisor_sw_interrupt: BitField<Reserved<0, Bin>, 1, 1>,
     virtual_supervisor_sw_interrupt: BitField<Bin, 2, 2>,
@@ -61,8 +80,49 @@ pub struct Hideleg {
     custom: BitField<Hex, 16, 63>,
 }
 
+/// Hypervisor Interrupt Bitmap
+#[derive(Csr)]
+#[csr(regs(hie = 0x604, hip = 0x644))]
+pub struct Hinterrupts {
+    vs_software_i: BitField<Bin, 2, 2>,
+    vs_timer_i: BitField<Bin, 6, 6>,
+    vs_external_i: BitField<Bin, 10, 10>,
+    sg_external_i: BitField<Bin, 12, 12>,
+}
+
+/// Hypervisor Interrupt Enable Register
+pub type Hie = Hinterrupts;
+
+/// Hypervisor Interrupt Pending Register
+pub type Hip = Hinterrupts;
+
+/// Hypervisor Virtual Interrupt Pending Register
+///
+/// _Unlike hie & hip it has no supervisor guest external interrupt bit_
+#[derive(Csr)]
+#[csr(addr = 0x645, name = "hvip")]
+pub struct Hvip {
+    vs_software_i: BitField<Bin, 2, 2>,
+    vs_timer_i: BitField<Bin, 6, 6>,
+    vs_external_i: BitField<Bin, 10, 10>,
+}
+
+/// Hypervisor Guest External Interrupt Bitmap
+#[derive(Csr)]
+#[csr(regs(hgeie = 0x607, hgeip = 0xe12))]
+pub struct Hgeinterrupts {
+    guest_external_i: BitField<Hex, 1, 63>,
+}
+
+/// Hypervisor Guest External Interrupt Enable Register
+pub type Hgeie = Hgeinterrupts;
+
+/// Hypervisor Guest External Interrupt Pending Register
+pub type Hgeip = Hgeinterrupts;
+
 /// Hypervisor Counter Enable Register
 #[derive(Csr)]
+#[csr(addr = 0x606, name = "hcounteren")]
 pub struct Hcounteren {
     hpm31: BitField<Bin, 31, 31>,
     hpm30: BitField<Bin, 30, 30>,
@@ -100,18 +160,21 @@ pub struct Hcounteren {
 
 /// Hypervisor Trap Value Register
 #[derive(Csr)]
+#[csr(addr = 0x643, name = "htval")]
 pub struct Htval {
     val: BitField<Hex, 0, 63>,
 }
 
 ///Hypervisor Trap Instruction Register
 #[derive(Csr)]
+#[csr(addr = 0x64a, name = "htinst")]
 pub struct Htinst {
     inst: BitField<Hex, 0, 63>,
 }
 
 /// Hypervisor Environment Configuration Register
 #[derive(Csr)]
+#[csr(addr = 0x60a, name = "henvcfg")]
 pub struct Henvcfg {
     stce: BitField<Bin, 63, 63>,
     pbmte: BitField<Bin, 62, 62>,
@@ -121,10 +184,47 @@ pub struct Henvcfg {
     fiom: BitField<Bin, 0, 0>,
 }
 
+/// RV32 Hypervisor Environment Configuration Register
+#[derive(Csr)]
+#[csr(rename = "henvcfg")]
+#[csr(addr = 0x60a, name = "henvcfg", xlen = 32)]
+pub struct Henvcfg32 {
+    cbze: BitField<Bin, 7, 7>,
+    cbcfe: BitField<Bin, 6, 6>,
+    cbie: BitField<Bin, 4, 5>,
+    fiom: BitField<Bin, 0, 0>,
+}
+
+/// RV32 upper half of the Hypervisor Environment Configuration Register
+#[derive(Csr)]
+#[csr(addr = 0x61a, name = "henvcfgh", xlen = 32)]
+pub struct Henvcfgh {
+    stce: BitField<Bin, 31, 31>,
+    pbmte: BitField<Bin, 30, 30>,
+}
+
 /// Hypervisor Guest Address Translation & Protection Register
 #[derive(Csr)]
+#[csr(addr = 0x680, name = "hgatp")]
 pub struct Hgatp {
     mode: BitField<Atp, 60, 63>,
     vmid: BitField<Hex, 44, 57>,
     ppn: BitField<Hex, 0, 43>,
 }
+
+/// RV32 Hypervisor Guest Address Translation & Protection Register
+#[derive(Csr)]
+#[csr(rename = "hgatp")]
+#[csr(addr = 0x680, name = "hgatp", xlen = 32)]
+pub struct Hgatp32 {
+    mode: BitField<Atp, 31, 31>,
+    vmid: BitField<Hex, 22, 28>,
+    ppn: BitField<Hex, 0, 21>,
+}
+
+/// Hypervisor Time Delta Register
+#[derive(Csr)]
+#[csr(addr = 0x605, name = "htimedelta")]
+pub struct Htimedelta {
+    delta: BitField<Signed, 0, 63>,
+}
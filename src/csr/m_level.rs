@@ -1,13 +1,14 @@
 //! Definitions for the machine mode CSRs
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
 use super::Csr;
-use crate::bitfield::{Arch, Bin, Hex, Priv, Tvec};
+use crate::bitfield::{Arch, Bin, Bool, ExcCode, ExtState, Hex, Priv, Tvec};
 use crate::bitfield::{BitField, RSh};
 
 /// Machine ISA Register
 #[derive(Csr)]
+#[csr(addr = 0x301, name = "misa")]
 pub struct Misa {
     a: BitField<Bin, 0, 0>,
     b: BitField<Bin, 1, 1>,
@@ -38,8 +39,43 @@ pub struct Misa {
     mxl: BitField<Arch, 62, 63>,
 }
 
+/// RV32 Machine ISA Register
+#[derive(Csr)]
+#[csr(rename = "misa")]
+#[csr(addr = 0x301, name = "misa", xlen = 32)]
+pub struct Misa32 {
+    a: BitField<Bin, 0, 0>,
+    b: BitField<Bin, 1, 1>,
+    c: BitField<Bin, 2, 2>,
+    d: BitField<Bin, 3, 3>,
+    e: BitField<Bin, 4, 4>,
+    f: BitField<Bin, 5, 5>,
+    g: BitField<Bin, 6, 6>,
+    h: BitField<Bin, 7, 7>,
+    i: BitField<Bin, 8, 8>,
+    j: BitField<Bin, 9, 9>,
+    k: BitField<Bin, 10, 10>,
+    l: BitField<Bin, 11, 11>,
+    m: BitField<Bin, 12, 12>,
+    n: BitField<Bin, 13, 13>,
+    o: BitField<Bin, 14, 14>,
+    p: BitField<Bin, 15, 15>,
+    q: BitField<Bin, 16, 16>,
+    r: BitField<Bin, 17, 17>,
+    s: BitField<Bin, 18, 18>,
+    t: BitField<Bin, 19, 19>,
+    u: BitField<Bin, 20, 20>,
+    v: BitField<Bin, 21, 21>,
+    w: BitField<Bin, 22, 22>,
+    x: BitField<Bin, 23, 23>,
+    y: BitField<Bin, 24, 24>,
+    z: BitField<Bin, 25, 25>,
+    mxl: BitField<Arch, 30, 31>,
+}
+
 /// Machine Vendor ID Register
 #[derive(Csr)]
+#[csr(addr = 0xf11, name = "mvendorid")]
 pub struct Mvendorid {
     offset: BitField<Hex, 0, 6>,
     bank: BitField<Hex, 7, 31>,
@@ -47,24 +83,28 @@ pub struct Mvendorid {
 
 /// Machine Architecture ID Register
 #[derive(Csr)]
+#[csr(addr = 0xf12, name = "marchid")]
 pub struct Marchid {
     id: BitField<Hex, 0, 63>,
 }
 
 /// Machine Implementation ID Register
 #[derive(Csr)]
+#[csr(addr = 0xf13, name = "mimpid")]
 pub struct Mimpid {
     id: BitField<Hex, 0, 63>,
 }
 
 /// Hart ID Register                         
 #[derive(Csr)]
+#[csr(addr = 0xf14, name = "mhartid")]
 pub struct Mhartid {
     id: BitField<Hex, 0, 63>,
 }
 
 /// Machine Status Register                  
 #[derive(Csr)]
+#[csr(addr = 0x300, name = "mstatus")]
 pub struct Mstatus {
     sie: BitField<Bin, 1, 1>,
     mie: BitField<Bin, 3, 3>,
@@ -72,9 +112,9 @@ pub struct Mstatus {
     ube: BitField<Bin, 6, 6>,
     mpie: BitField<Bin, 7, 7>,
     spp: BitField<Priv, 8, 8>,
-    vs: BitField<Bin, 9, 10>,
+    vs: BitField<ExtState, 9, 10>,
     mpp: BitField<Priv, 11, 12>,
-    fs: BitField<Bin, 13, 14>,
+    fs: BitField<ExtState, 13, 14>,
     xs: BitField<Bin, 15, 16>,
     mprv: BitField<Bin, 17, 17>,
     sum: BitField<Bin, 18, 18>,
@@ -91,8 +131,112 @@ pub struct Mstatus {
     sd: BitField<Bin, 63, 63>,
 }
 
+/// RV32 Machine Status Register
+#[derive(Csr)]
+#[csr(rename = "mstatus")]
+#[csr(addr = 0x300, name = "mstatus", xlen = 32)]
+pub struct Mstatus32 {
+    sie: BitField<Bin, 1, 1>,
+    mie: BitField<Bin, 3, 3>,
+    spie: BitField<Bin, 5, 5>,
+    ube: BitField<Bin, 6, 6>,
+    mpie: BitField<Bin, 7, 7>,
+    spp: BitField<Priv, 8, 8>,
+    vs: BitField<ExtState, 9, 10>,
+    mpp: BitField<Priv, 11, 12>,
+    fs: BitField<ExtState, 13, 14>,
+    xs: BitField<Bin, 15, 16>,
+    mprv: BitField<Bin, 17, 17>,
+    sum: BitField<Bin, 18, 18>,
+    mxr: BitField<Bin, 19, 19>,
+    tvm: BitField<Bin, 20, 20>,
+    tw: BitField<Bin, 21, 21>,
+    tsr: BitField<Bin, 22, 22>,
+    sd: BitField<Bin, 31, 31>,
+}
+
+/// RV32 upper half of the Machine Status Register
+#[derive(Csr)]
+#[csr(addr = 0x310, name = "mstatush", xlen = 32)]
+pub struct Mstatush {
+    sbe: BitField<Bin, 4, 4>,
+    mbe: BitField<Bin, 5, 5>,
+    gva: BitField<Bin, 6, 6>,
+    mpv: BitField<Bin, 7, 7>,
+}
+
+/// RV32 Machine Status Register joined with its upper half, i.e. `mstatush << 32 | mstatus`
+#[derive(Csr)]
+#[csr(rename = "mstatus")]
+pub struct MstatusPair {
+    sie: BitField<Bin, 1, 1>,
+    mie: BitField<Bin, 3, 3>,
+    spie: BitField<Bin, 5, 5>,
+    ube: BitField<Bin, 6, 6>,
+    mpie: BitField<Bin, 7, 7>,
+    spp: BitField<Priv, 8, 8>,
+    vs: BitField<ExtState, 9, 10>,
+    mpp: BitField<Priv, 11, 12>,
+    fs: BitField<ExtState, 13, 14>,
+    xs: BitField<Bin, 15, 16>,
+    mprv: BitField<Bin, 17, 17>,
+    sum: BitField<Bin, 18, 18>,
+    mxr: BitField<Bin, 19, 19>,
+    tvm: BitField<Bin, 20, 20>,
+    tw: BitField<Bin, 21, 21>,
+    tsr: BitField<Bin, 22, 22>,
+    sd: BitField<Bin, 31, 31>,
+    sbe: BitField<Bin, 36, 36>,
+    mbe: BitField<Bin, 37, 37>,
+    gva: BitField<Bin, 38, 38>,
+    mpv: BitField<Bin, 39, 39>,
+}
+
+/// Machine Environment Configuration Register
+#[derive(Csr)]
+#[csr(addr = 0x30a, name = "menvcfg")]
+pub struct Menvcfg {
+    stce: BitField<Bin, 63, 63>,
+    pbmte: BitField<Bin, 62, 62>,
+    cbze: BitField<Bin, 7, 7>,
+    cbcfe: BitField<Bin, 6, 6>,
+    cbie: BitField<Bin, 4, 5>,
+    fiom: BitField<Bin, 0, 0>,
+}
+
+/// RV32 Machine Environment Configuration Register
+#[derive(Csr)]
+#[csr(rename = "menvcfg")]
+#[csr(addr = 0x30a, name = "menvcfg", xlen = 32)]
+pub struct Menvcfg32 {
+    cbze: BitField<Bin, 7, 7>,
+    cbcfe: BitField<Bin, 6, 6>,
+    cbie: BitField<Bin, 4, 5>,
+    fiom: BitField<Bin, 0, 0>,
+}
+
+/// Machine Security Configuration Register
+#[derive(Csr)]
+#[csr(addr = 0x747, name = "mseccfg")]
+pub struct Mseccfg {
+    mml: BitField<Bin, 0, 0>,
+    mmwp: BitField<Bin, 1, 1>,
+    rlb: BitField<Bin, 2, 2>,
+    useed: BitField<Bin, 8, 8>,
+    sseed: BitField<Bin, 9, 9>,
+}
+
+/// RV32 upper half of the Machine Environment Configuration Register
+#[derive(Csr)]
+#[csr(addr = 0x31a, name = "menvcfgh", xlen = 32)]
+pub struct Menvcfgh {
+    stce: BitField<Bin, 31, 31>,
+    pbmte: BitField<Bin, 30, 30>,
+}
+
 /// Machine Trap-Vector Base-Address Register
 #[derive(Csr)]
+#[csr(addr = 0x305, name = "mtvec")]
 pub struct Mtvec {
     base: BitField<Hex, 2, 63>,
     mode: BitField<Tvec, 0, 1>,
@@ -100,6 +244,7 @@ pub struct Mtvec {
 
 /// Machine Exception Delegation Register
 #[derive(Csr)]
+#[csr(addr = 0x302, name = "medeleg")]
 pub struct Medeleg {
     misaligned_fetch: BitField<Bin, 0x0, 0x0>,
     fetch_access: BitField<Bin, 0x1, 0x1>,
@@ -122,20 +267,101 @@ pub struct Medeleg {
     store_guest_page_fault: BitField<Bin, 0x17, 0x17>,
 }
 
+/// Machine Counter-Enable Register
+#[derive(Csr)]
+#[csr(addr = 0x306, name = "mcounteren")]
+pub struct Mcounteren {
+    hpm31: BitField<Bin, 31, 31>,
+    hpm30: BitField<Bin, 30, 30>,
+    hpm29: BitField<Bin, 29, 29>,
+    hpm28: BitField<Bin, 28, 28>,
+    hpm27: BitField<Bin, 27, 27>,
+    hpm26: BitField<Bin, 26, 26>,
+    hpm25: BitField<Bin, 25, 25>,
+    hpm24: BitField<Bin, 24, 24>,
+    hpm23: BitField<Bin, 23, 23>,
+    hpm22: BitField<Bin, 22, 22>,
+    hpm21: BitField<Bin, 21, 21>,
+    hpm20: BitField<Bin, 20, 20>,
+    hpm19: BitField<Bin, 19, 19>,
+    hpm18: BitField<Bin, 18, 18>,
+    hpm17: BitField<Bin, 17, 17>,
+    hpm16: BitField<Bin, 16, 16>,
+    hpm15: BitField<Bin, 15, 15>,
+    hpm14: BitField<Bin, 14, 14>,
+    hpm13: BitField<Bin, 13, 13>,
+    hpm12: BitField<Bin, 12, 12>,
+    hpm11: BitField<Bin, 11, 11>,
+    hpm10: BitField<Bin, 10, 10>,
+    hpm9: BitField<Bin, 9, 9>,
+    hpm8: BitField<Bin, 8, 8>,
+    hpm7: BitField<Bin, 7, 7>,
+    hpm6: BitField<Bin, 6, 6>,
+    hpm5: BitField<Bin, 5, 5>,
+    hpm4: BitField<Bin, 4, 4>,
+    hpm3: BitField<Bin, 3, 3>,
+    ir: BitField<Bin, 2, 2>,
+    tm: BitField<Bin, 1, 1>,
+    cy: BitField<Bin, 0, 0>,
+}
+
+/// Machine Scratch Register
+#[derive(Csr)]
+#[csr(addr = 0x340, name = "mscratch")]
+pub struct Mscratch {
+    mscratch: BitField<Hex, 0, 63>,
+}
+
+/// Machine Exception Program Counter
+#[derive(Csr)]
+#[csr(addr = 0x341, name = "mepc")]
+pub struct Mepc {
+    mepc: BitField<Hex, 0, 63>,
+}
+
+/// Machine trap Cause Register
+#[derive(Csr)]
+#[csr(addr = 0x342, name = "mcause")]
+pub struct Mcause {
+    interrupt: BitField<Bool, 63, 63>,
+    /* Note: as for scause, the interrupt flag is included into the exception code bitfield
+    for formatting purposes */
+    code: BitField<ExcCode, 0, 63>,
+}
+
+/// RV32 Machine trap Cause Register
+#[derive(Csr)]
+#[csr(rename = "mcause")]
+#[csr(addr = 0x342, name = "mcause", xlen = 32)]
+pub struct Mcause32 {
+    interrupt: BitField<Bool, 31, 31>,
+    code: BitField<ExcCode, 0, 31>,
+}
+
 /// Machine Trap Value Register
 #[derive(Csr)]
+#[csr(addr = 0x343, name = "mtval")]
 pub struct Mtval {
     tval: BitField<Hex, 0, 63>,
 }
 
 /// Machine Trap Value Register
 #[derive(Csr)]
+#[csr(addr = 0x34b, name = "mtval2")]
 pub struct Mtval2 {
     tval: BitField<RSh<2, Hex>, 0, 63>,
 }
 
+/// Machine Trap Instruction Register
+#[derive(Csr)]
+#[csr(addr = 0x34a, name = "mtinst")]
+pub struct Mtinst {
+    inst: BitField<Hex, 0, 63>,
+}
+
 /// Machine Interrupt Bitmap
 #[derive(Csr)]
+#[csr(regs(mideleg = 0x303, mie = 0x304, mip = 0x344))]
 pub struct Minterrupts {
     s_software_i: BitField<Bin, 1, 1>,
     vs_software_i: BitField<Bin, 2, 2>,
@@ -1,9 +1,13 @@
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 use crate::encoding;
 use crate::format::Csr;
 
 // Export CSRs
+mod u_level;
+pub use u_level::*;
 mod m_level;
 pub use m_level::*;
 mod h_level;
@@ -16,25 +20,118 @@ pub use pmp::*;
 /// Represents a CSR address
 type Addr = u16;
 
+/// A CSR address & name that a struct is declared for with `#[csr(addr = .., name = ..)]` or
+/// `#[csr(regs(NAME = ADDR, ..))]`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub addr: Addr,
+    pub name: &'static str,
+    /// Only use the struct on harts with this XLEN, e.g. for an RV32 layout
+    pub xlen: Option<usize>,
+}
+
 /// Errors that may arise when creating/handling a CSR
+#[cfg(feature = "std")]
 #[derive(Error, Debug)]
 pub enum CsrError {
     #[error("\"{0}\" is not a name of a supported CSR")]
     UnknownName(String),
     #[error("0x{0:03x} is not an address of a supported CSR")]
     UnkownAddr(Addr),
+    #[error("0x{0:x} does not fit in a {1} bit CSR")]
+    TooWide(u64, usize),
+    #[error("0x{0:03x} is not an address of an RV32 CSR with an upper half")]
+    NoHigh(Addr),
+    #[error("{0} is not a supported XLEN, use 32 or 64")]
+    Xlen(usize),
+}
+
+/// Names of CSRs without a layout, only looked up by address if no [REGISTRY] entry matches
+///
+/// _Numbered CSRs are listed in [NUMBERED]_
+#[cfg(feature = "std")]
+const UNDECODED: &[&str] = &[
+    // Unprivileged
+    "fflags",
+    "frm",
+    "fcsr",
+    "cycleh",
+    "timeh",
+    "instreth",
+    // Supervisor
+    "scontext",
+    // Hypervisor
+    "hcontext",
+    "htimedeltah",
+    // Machine
+    "mconfigptr",
+    "mseccfgh",
+    "mcycleh",
+    "minstreth",
+    "mcountinhibit",
+    // Debug
+    "tselect",
+    "tdata1",
+    "tdata2",
+    "tdata3",
+    "dcsr",
+    "dpc",
+    "dscratch0",
+    "dscratch1",
+];
+
+/// Numbered CSR names without a layout as `(prefix, first number, last number, suffix)`
+#[cfg(feature = "std")]
+const NUMBERED: &[(&str, usize, usize, &str)] = &[
+    ("hpmcounter", 3, 31, "h"),
+    ("mhpmcounter", 3, 31, "h"),
+    ("mhpmevent", 3, 31, ""),
+];
+
+/// Get the name of the CSR at an address
+#[cfg(feature = "std")]
+pub fn name(address: Addr) -> Option<String> {
+    let declared = REGISTRY
+        .iter()
+        .flat_map(|(entries, _)| entries.iter())
+        .find(|e| e.addr == address);
+    if let Some(e) = declared {
+        return Some(e.name.to_string());
+    }
+    let numbered = NUMBERED.iter().flat_map(|&(prefix, first, last, suffix)| {
+        (first..=last).map(move |n| format!("{prefix}{n}{suffix}"))
+    });
+    UNDECODED
+        .iter()
+        .map(|n| n.to_string())
+        .chain(numbered)
+        .find(|n| encoding::csr_address_map(n) == Some(address))
 }
 
 /// Convert a name/address string to a valid [Addr]
+///
+/// _Addresses are 12 bits wide, larger ones are rejected_
+#[cfg(feature = "std")]
 fn addr(csr_str: &str) -> Result<Addr, CsrError> {
-    if let Some(addr_str) = csr_str.strip_prefix("0x") {
-        let a = Addr::from_str_radix(addr_str, 16);
-        Ok(a.expect("A 0x prefixed string should be a valid hexadecimal number"))
+    let unknown = |_| CsrError::UnknownName(csr_str.to_string());
+    let address = if let Some(addr_str) = csr_str.strip_prefix("0x") {
+        Some(Addr::from_str_radix(addr_str, 16).map_err(unknown)?)
     } else if let Some(addr_str) = csr_str.strip_prefix("0b") {
-        let a = Addr::from_str_radix(addr_str, 2);
-        Ok(a.expect("A 0b prefixed string should be a valid binary number"))
-    } else if let Ok(a) = Addr::from_str_radix(csr_str, 10) {
-        Ok(a)
+        Some(Addr::from_str_radix(addr_str, 2).map_err(unknown)?)
+    } else {
+        Addr::from_str_radix(csr_str, 10).ok()
+    };
+    if let Some(a) = address {
+        match a {
+            0..=0xfff => Ok(a),
+            _ => Err(CsrError::UnkownAddr(a)),
+        }
+    } else if let Some(e) = REGISTRY
+        .iter()
+        .flat_map(|(entries, _)| entries.iter())
+        .find(|e| e.name == csr_str)
+    {
+        Ok(e.addr)
     } else if let Some(a) = encoding::csr_address_map(csr_str) {
         Ok(a)
     } else {
@@ -42,92 +139,199 @@ fn addr(csr_str: &str) -> Result<Addr, CsrError> {
     }
 }
 
-/// Build a CSR [Format] from an address and value
-pub fn format(csr_str: &str, value: u64) -> Result<Box<dyn Csr>, CsrError> {
-    let address = match addr(csr_str) {
-        Ok(a) => a,
-        Err(e) => return Err(e),
-    };
+/// Build a CSR [Format] from an address and value for harts with the given XLEN, along with
+/// the name it is registered under at that address
+///
+/// _RV32 harts use the RV64 layout of CSRs that have no bits above 31_
+#[cfg(feature = "std")]
+pub fn format(
+    csr_str: &str,
+    value: u64,
+    xlen: usize,
+) -> Result<(&'static str, Box<dyn Csr>), CsrError> {
+    let address = addr(csr_str)?;
+    match xlen {
+        32 if value > u32::MAX as u64 => Err(CsrError::TooWide(value, 32)),
+        32 | 64 => lookup(address, xlen)
+            .map(|(name, build)| (name, build(value)))
+            .ok_or(CsrError::UnkownAddr(address)),
+        _ => Err(CsrError::Xlen(xlen)),
+    }
+}
+
+/// Build a CSR [Format] & its registered name from both halves of an RV32 CSR that is split
+/// across two registers
+///
+/// _The halves are joined and decoded with a layout of both RV32 halves_
+#[cfg(feature = "std")]
+pub fn format_pair(
+    csr_str: &str,
+    low: u64,
+    high: u64,
+) -> Result<(&'static str, Box<dyn Csr>), CsrError> {
+    let address = addr(csr_str)?;
+    if let Some(&wide) = [low, high].iter().find(|&&v| v > u32::MAX as u64) {
+        return Err(CsrError::TooWide(wide, 32));
+    }
+    let (_, joined) = high_half(address).ok_or(CsrError::NoHigh(address))?;
+    let (name, _) = lookup(address, 32).ok_or(CsrError::UnkownAddr(address))?;
+    Ok((name, joined(high << 32 | low)))
+}
+
+/// Get the address of the CSR holding the upper half of an RV32 CSR & how to build both halves
+/// joined
+///
+/// _Only mstatus moves fields when split, the others keep the RV64 bit positions in the upper half_
+#[cfg(feature = "std")]
+fn high_half(address: Addr) -> Option<(Addr, Build)> {
     match address {
-        // Unprivileged counters & timers
-        encoding::CSR_CYCLE => todo!("Add cycle format"),
-        encoding::CSR_TIME => todo!("Add time format"),
-        encoding::CSR_INSTRET => todo!("Add instret format"),
-        encoding::CSR_HPMCOUNTER3..=encoding::CSR_HPMCOUNTER31 => {
-            todo!("Add hpmcounter format")
+        encoding::CSR_CYCLE..=encoding::CSR_HPMCOUNTER31
+        | encoding::CSR_MCYCLE..=encoding::CSR_MHPMCOUNTER31 => {
+            Some((address + 0x80, build::<Counter>))
         }
-        // Supervisor trap setup
-        encoding::CSR_SSTATUS => Ok(Box::new(Sstatus::new(value))),
-        encoding::CSR_SIE => Ok(Box::new(Sie::new(value))),
-        encoding::CSR_STVEC => Ok(Box::new(Stvec::new(value))),
-        encoding::CSR_SCOUNTEREN => Ok(Box::new(Scounteren::new(value))),
-        // Supervisor config
-        encoding::CSR_SENVCFG => Ok(Box::new(Senvcfg::new(value))),
-        // Supervisor trap handling
-        encoding::CSR_SSCRATCH => Ok(Box::new(Sscratch::new(value))),
-        encoding::CSR_SEPC => Ok(Box::new(Sepc::new(value))),
-        encoding::CSR_SCAUSE => Ok(Box::new(Scause::new(value))),
-        encoding::CSR_STVAL => Ok(Box::new(Mtval::new(value))),
-        encoding::CSR_SIP => Ok(Box::new(Sip::new(value))),
-        // Supervisor Protection and Translation
-        encoding::CSR_SATP => Ok(Box::new(Satp::new(value))),
-        // Hypervisor Trap Setup
-        encoding::CSR_HSTATUS => Ok(Box::new(Hstatus::new(value))),
-        encoding::CSR_HEDELEG => Ok(Box::new(Hedeleg::new(value))),
-        encoding::CSR_HIDELEG => Ok(Box::new(Hideleg::new(value))),
-        encoding::CSR_HIE => todo!("Add hie format"),
-        encoding::CSR_HCOUNTEREN => Ok(Box::new(Hcounteren::new(value))),
-        encoding::CSR_HGEIE => todo!("Add hgeie format"),
-        // Hypervisor Trap Handling
-        encoding::CSR_HTVAL => todo!("Add htval format"),
-        encoding::CSR_HIP => todo!("Add hip format"),
-        encoding::CSR_HVIP => todo!("Add hvip format"),
-        encoding::CSR_HTINST => todo!("Add htinst format"),
-        encoding::CSR_HGEIP => todo!("Add hgeip format"),
-        // Hypervisor configuration
-        encoding::CSR_HENVCFG => Ok(Box::new(Henvcfg::new(value))),
-        // Hypervisor Protection and Translation
-        encoding::CSR_HGATP => Ok(Box::new(Hgatp::new(value))),
-        // Hypervisor Counter/Timer Virtualization Registers
-        encoding::CSR_HTIMEDELTA => todo!("Add htimedelta format"),
-        // Virtual Supervisor Registers
-        encoding::CSR_VSSTATUS => Ok(Box::new(Sstatus::new(value))),
-        encoding::CSR_VSIE => todo!("Add vsie format"),
-        encoding::CSR_VSTVEC => Ok(Box::new(Stvec::new(value))),
-        encoding::CSR_VSSCRATCH => Ok(Box::new(Sscratch::new(value))),
-        encoding::CSR_VSEPC => Ok(Box::new(Sepc::new(value))),
-        encoding::CSR_VSCAUSE => Ok(Box::new(Scause::new(value))),
-        encoding::CSR_VSTVAL => todo!("Add vstval format"),
-        encoding::CSR_VSIP => todo!("Add vsip format"),
-        encoding::CSR_VSATP => Ok(Box::new(Satp::new(value))),
-        // Machine Information Registers
-        encoding::CSR_MVENDORID => Ok(Box::new(Mvendorid::new(value))),
-        encoding::CSR_MARCHID => Ok(Box::new(Marchid::new(value))),
-        encoding::CSR_MIMPID => Ok(Box::new(Mimpid::new(value))),
-        encoding::CSR_MHARTID => Ok(Box::new(Mhartid::new(value))),
-        // Machine Trap Setup
-        encoding::CSR_MSTATUS => Ok(Box::new(Mstatus::new(value))),
-        encoding::CSR_MISA => Ok(Box::new(Misa::new(value))),
-        encoding::CSR_MEDELEG => Ok(Box::new(Medeleg::new(value))),
-        encoding::CSR_MIDELEG => Ok(Box::new(Mideleg::new(value))),
-        encoding::CSR_MIE => Ok(Box::new(Mie::new(value))),
-        encoding::CSR_MTVEC => Ok(Box::new(Mtvec::new(value))),
-        encoding::CSR_MCOUNTEREN => todo!("Add mcounteren format"),
-        // Machine Trap Handling
-        encoding::CSR_MSCRATCH => todo!("Add mscratch format"),
-        encoding::CSR_MEPC => todo!("Add mepc format"),
-        encoding::CSR_MCAUSE => todo!("Add mcause format"),
-        encoding::CSR_MTVAL => Ok(Box::new(Mtval::new(value))),
-        encoding::CSR_MIP => Ok(Box::new(Mip::new(value))),
-        encoding::CSR_MTINST => todo!("Add mtinst format"),
-        encoding::CSR_MTVAL2 => Ok(Box::new(Mtval2::new(value))),
-        // Machine Configuration
-        encoding::CSR_MENVCFG => todo!("Add machine environment config format"),
-        encoding::CSR_MSECCFG => todo!("Add machine security config format"),
-        // Physical memory protection
-        encoding::CSR_PMPADDR0..=encoding::CSR_PMPADDR63 => Ok(Box::new(PmpAddr::new(value))),
-        encoding::CSR_PMPCFG0..=encoding::CSR_PMPCFG15 => Ok(Box::new(PmpCfg::new(value))),
-        // Unkown CSR
-        _ => Err(CsrError::UnkownAddr(address)),
+        encoding::CSR_MSTATUS => Some((encoding::CSR_MSTATUSH, build::<MstatusPair>)),
+        encoding::CSR_MENVCFG => Some((encoding::CSR_MENVCFGH, build::<Menvcfg>)),
+        encoding::CSR_HENVCFG => Some((encoding::CSR_HENVCFGH, build::<Henvcfg>)),
+        encoding::CSR_HTIMEDELTA => Some((encoding::CSR_HTIMEDELTAH, build::<Htimedelta>)),
+        encoding::CSR_PMPCFG0..=encoding::CSR_PMPCFG15 if address & 1 == 0 => {
+            Some((address + 1, build::<PmpCfg>))
+        }
+        _ => None,
+    }
+}
+
+/// Builds a boxed CSR from its value
+#[cfg(feature = "std")]
+type Build = fn(u64) -> Box<dyn Csr>;
+
+/// CSR structs with their [Entry]s & how to build them
+///
+/// _Collected from the structs declared with `#[csr(...)]` attributes by the `Csr` derive_
+#[cfg(feature = "std")]
+#[linkme::distributed_slice]
+static REGISTRY: [(&'static [Entry], Build)];
+
+/// Build a boxed CSR
+#[cfg(feature = "std")]
+fn build<T: Csr + 'static>(value: u64) -> Box<dyn Csr> {
+    Box::new(T::new(value))
+}
+
+/// Find the name of the CSR at an address & how to build it for harts with the given XLEN
+///
+/// _Structs declared for the XLEN take precedence over those declared for any XLEN_
+#[cfg(feature = "std")]
+fn lookup(address: Addr, xlen: usize) -> Option<(&'static str, Build)> {
+    let declared = |xlen| {
+        REGISTRY.iter().find_map(|&(entries, build)| {
+            let e = entries
+                .iter()
+                .find(|e| e.addr == address && e.xlen == xlen)?;
+            Some((e.name, build))
+        })
+    };
+    declared(Some(xlen)).or_else(|| declared(None))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fields(name: &str, value: u64, xlen: usize) -> Vec<(&'static str, String)> {
+        let (_, csr) = format(name, value, xlen).unwrap();
+        csr.fields()
+            .map(|f| (f.name, f.decoded.to_string()))
+            .collect()
+    }
+
+    fn fields_pair(name: &str, low: u64, high: u64) -> Vec<(&'static str, String)> {
+        let (_, csr) = format_pair(name, low, high).unwrap();
+        csr.fields()
+            .map(|f| (f.name, f.decoded.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn registry_entries() {
+        let entries: Vec<_> = REGISTRY.iter().flat_map(|(e, _)| e.iter()).collect();
+        for (i, e) in entries.iter().enumerate() {
+            if let Some(address) = encoding::csr_address_map(e.name) {
+                assert_eq!(address, e.addr, "{} is at 0x{:03x}", e.name, address);
+            }
+            let twice = entries[..i]
+                .iter()
+                .any(|d| d.addr == e.addr && d.xlen == e.xlen);
+            assert!(!twice, "{} is declared twice", e.name);
+        }
+        assert_eq!(format("stval", 0, 64).unwrap().0, "stval");
+        assert_eq!(format("vsstatus", 0, 64).unwrap().0, "vsstatus");
+        assert_eq!(format("hpmcounter31", 0, 32).unwrap().0, "hpmcounter31");
+        assert_eq!(format("0x3a2", 0, 32).unwrap().0, "pmpcfg2");
+        assert!(format("0xb01", 0, 64).is_err());
+        let hvip: Vec<_> = fields("hvip", 0x1444, 64)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(hvip, ["vs_software_i", "vs_timer_i", "vs_external_i"]);
+        assert!(fields("hip", 0x1000, 64).contains(&("sg_external_i", "0b1".to_string())));
+        assert_eq!(name(0x200).as_deref(), Some("vsstatus"));
+        assert_eq!(name(0x3a2).as_deref(), Some("pmpcfg2"));
+        assert_eq!(name(0x001).as_deref(), Some("fflags"));
+        assert_eq!(name(0xb83).as_deref(), Some("mhpmcounter3h"));
+        assert_eq!(name(0x7ff), None);
+        for n in UNDECODED {
+            let address = encoding::csr_address_map(n).unwrap();
+            assert!(lookup(address, 64).is_none(), "{} has a layout", n);
+        }
+    }
+
+    #[test]
+    fn rv32_layouts() {
+        let satp = fields("satp", 0x8040_0123, 32);
+        assert_eq!(satp[0], ("mode", "Sv32".to_string()));
+        assert_eq!(satp[1], ("asid", "0x1".to_string()));
+        let scause = fields("scause", 0x8000_0005, 32);
+        assert_eq!(scause[1].1, "Supervisor timer interrupt");
+        let misa = fields("misa", 0x4000_0100, 32);
+        assert_eq!(misa.last().unwrap().1, "RV32");
+        assert!(fields("mstatus", 0, 32).iter().all(|(n, _)| *n != "uxl"));
+        for name in ["mstatus", "mcause", "satp", "hgatp"] {
+            assert_eq!(format(name, 0, 32).unwrap().1.name(), name);
+        }
+        assert_eq!(format("pmpcfg2", 0, 32).unwrap().1.name(), "pmpcfg");
+    }
+
+    #[test]
+    fn rv32_pair() {
+        let mstatus = fields_pair("mstatus", 0x1800, 0x30);
+        assert!(mstatus.contains(&("mpp", "Machine".to_string())));
+        assert!(mstatus.contains(&("mbe", "0b1".to_string())));
+        let mstatus = fields_pair("mstatus", 0x80006000, 0);
+        assert!(mstatus.contains(&("sd", "0b1".to_string())));
+        assert!(!mstatus
+            .iter()
+            .any(|(name, _)| *name == "uxl" || *name == "sxl"));
+        assert!(fields_pair("menvcfg", 0, 0x8000_0000).contains(&("stce", "0b1".to_string())));
+        assert_eq!(format_pair("pmpcfg2", 0, 0).unwrap().0, "pmpcfg2");
+        assert!(format_pair("satp", 0, 1).is_err());
+        assert!(format_pair("pmpcfg1", 0, 1).is_err());
+        assert!(format_pair("mstatus", 0, 0x1_0000_0000).is_err());
+    }
+
+    #[test]
+    fn rv32_value_too_wide() {
+        assert!(format("mstatus", 0x1_0000_0000, 32).is_err());
+        assert!(format("mstatush", 0, 64).is_err());
+    }
+
+    #[test]
+    fn malformed_addresses() {
+        assert!(matches!(addr("0xzz"), Err(CsrError::UnknownName(_))));
+        assert!(matches!(addr("0b12"), Err(CsrError::UnknownName(_))));
+        assert!(matches!(addr("0x10000"), Err(CsrError::UnknownName(_))));
+        assert!(matches!(addr("0x1000"), Err(CsrError::UnkownAddr(0x1000))));
+        assert!(matches!(addr("4096"), Err(CsrError::UnkownAddr(0x1000))));
+        assert_eq!(addr("0x300").unwrap(), 0x300);
+        assert_eq!(addr("768").unwrap(), 0x300);
     }
 }
@@ -1,6 +1,6 @@
 //! Definitions for CSRs related to physical memory protection
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
 use super::Csr;
 use crate::bitfield::BitField;
@@ -8,6 +8,7 @@ use crate::bitfield::{Hex, PmpXCfg, RSh, Reserved};
 
 /// Physical memory protection address register
 #[derive(Csr)]
+#[csr(addr = 0x3b0, name = "pmpaddr", count = 64)]
 pub struct PmpAddr {
     reserved: BitField<Reserved<0, Hex>, 54, 63>,
     address: BitField<RSh<2, Hex>, 0, 53>,
@@ -15,6 +16,7 @@ pub struct PmpAddr {
 
 /// Physical memory protection configuration register
 #[derive(Csr)]
+#[csr(addr = 0x3a0, name = "pmpcfg", count = 16)]
 pub struct PmpCfg {
     pmp0cfg: BitField<PmpXCfg, 0, 7>,
     pmp1cfg: BitField<PmpXCfg, 8, 15>,
@@ -25,3 +27,14 @@ pub struct PmpCfg {
     pmp6cfg: BitField<PmpXCfg, 48, 55>,
     pmp7cfg: BitField<PmpXCfg, 56, 63>,
 }
+
+/// RV32 physical memory protection configuration register
+#[derive(Csr)]
+#[csr(rename = "pmpcfg")]
+#[csr(addr = 0x3a0, name = "pmpcfg", xlen = 32, count = 16)]
+pub struct PmpCfg32 {
+    pmp0cfg: BitField<PmpXCfg, 0, 7>,
+    pmp1cfg: BitField<PmpXCfg, 8, 15>,
+    pmp2cfg: BitField<PmpXCfg, 16, 23>,
+    pmp3cfg: BitField<PmpXCfg, 24, 31>,
+}
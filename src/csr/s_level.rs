@@ -1,20 +1,21 @@
 //! Definitions for the supervisor mode CSRs
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
 use super::Csr;
 use crate::bitfield::BitField;
-use crate::bitfield::{Arch, Atp, Bin, Bool, ExcCode, Hex, Priv, RSh, Tvec};
+use crate::bitfield::{Arch, Atp, Bin, Bool, ExcCode, ExtState, Hex, Priv, RSh, Tvec};
 
 /// Supervisor Status Register
 #[derive(Csr)]
+#[csr(regs(sstatus = 0x100, vsstatus = 0x200))]
 pub struct Sstatus {
     sie: BitField<Bin, 1, 1>,
     spie: BitField<Bin, 5, 5>,
     ube: BitField<Bin, 6, 6>,
     spp: BitField<Priv, 8, 8>,
-    vs: BitField<Bin, 9, 10>,
-    fs: BitField<Bin, 13, 14>,
+    vs: BitField<ExtState, 9, 10>,
+    fs: BitField<ExtState, 13, 14>,
     xs: BitField<Bin, 15, 16>,
     sum: BitField<Bin, 18, 18>,
     mxr: BitField<Bin, 19, 19>,
@@ -22,8 +23,26 @@ pub struct Sstatus {
     sd: BitField<Bin, 63, 63>,
 }
 
+/// RV32 Supervisor Status Register
+#[derive(Csr)]
+#[csr(rename = "sstatus")]
+#[csr(regs(sstatus = 0x100, vsstatus = 0x200), xlen = 32)]
+pub struct Sstatus32 {
+    sie: BitField<Bin, 1, 1>,
+    spie: BitField<Bin, 5, 5>,
+    ube: BitField<Bin, 6, 6>,
+    spp: BitField<Priv, 8, 8>,
+    vs: BitField<ExtState, 9, 10>,
+    fs: BitField<ExtState, 13, 14>,
+    xs: BitField<Bin, 15, 16>,
+    sum: BitField<Bin, 18, 18>,
+    mxr: BitField<Bin, 19, 19>,
+    sd: BitField<Bin, 31, 31>,
+}
+
 /// Supervisor Interrupt Enable Register
 #[derive(Csr)]
+#[csr(regs(sie = 0x104, vsie = 0x204))]
 pub struct Sie {
     supervisor_sw_interrupt: BitField<Bin, 1, 1>,
     supervisor_timer_interrupt: BitField<Bin, 5, 5>,
@@ -32,6 +51,7 @@ pub struct Sie {
 
 /// Supervisor Interrupt Pending Register
 #[derive(Csr)]
+#[csr(regs(sip = 0x144, vsip = 0x244))]
 pub struct Sip {
     supervisor_sw_interrupt: BitField<Bin, 1, 1>,
     supervisor_timer_interrupt: BitField<Bin, 5, 5>,
@@ -40,6 +60,7 @@ pub struct Sip {
 
 /// Supervisor Environment Configuration Register
 #[derive(Csr)]
+#[csr(addr = 0x10a, name = "senvcfg")]
 pub struct Senvcfg {
     fiom: BitField<Bin, 0, 0>,
     cbie: BitField<Bin, 4, 5>,
@@ -48,14 +69,26 @@ pub struct Senvcfg {
 
 /// Supervisor Address Translation and Protection Register
 #[derive(Csr)]
+#[csr(regs(satp = 0x180, vsatp = 0x280))]
 pub struct Satp {
     mode: BitField<Atp, 60, 63>,
     asid: BitField<Hex, 44, 59>,
     ppn: BitField<RSh<12, Hex>, 0, 43>,
 }
 
+/// RV32 Supervisor Address Translation and Protection Register
+#[derive(Csr)]
+#[csr(rename = "satp")]
+#[csr(regs(satp = 0x180, vsatp = 0x280), xlen = 32)]
+pub struct Satp32 {
+    mode: BitField<Atp, 31, 31>,
+    asid: BitField<Hex, 22, 30>,
+    ppn: BitField<RSh<12, Hex>, 0, 21>,
+}
+
 /// Supervisor Trap Vector Base Address Register
 #[derive(Csr)]
+#[csr(regs(stvec = 0x105, vstvec = 0x205))]
 pub struct Stvec {
     base: BitField<Hex, 2, 63>,
     mode: BitField<Tvec, 0, 1>,
@@ -63,18 +96,21 @@ pub struct Stvec {
 
 /// Supervisor Scratch Register
 #[derive(Csr)]
+#[csr(regs(sscratch = 0x140, vsscratch = 0x240))]
 pub struct Sscratch {
     sscratch: BitField<Hex, 0, 63>,
 }
 
 /// Supervisor Exception Program Counter
 #[derive(Csr)]
+#[csr(regs(sepc = 0x141, vsepc = 0x241))]
 pub struct Sepc {
     sepc: BitField<Hex, 0, 63>,
 }
 
 /// Supervisor trap Cause Register
 #[derive(Csr)]
+#[csr(regs(scause = 0x142, vscause = 0x242))]
 pub struct Scause {
     interrupt: BitField<Bool, 63, 63>,
     /* Note: exeption code is scause[0,62] but for formatting purposes, the interrupt
@@ -82,8 +118,25 @@ pub struct Scause {
     code: BitField<ExcCode, 0, 63>,
 }
 
+/// RV32 Supervisor trap Cause Register
+#[derive(Csr)]
+#[csr(rename = "scause")]
+#[csr(regs(scause = 0x142, vscause = 0x242), xlen = 32)]
+pub struct Scause32 {
+    interrupt: BitField<Bool, 31, 31>,
+    code: BitField<ExcCode, 0, 31>,
+}
+
+/// Supervisor Trap Value Register
+#[derive(Csr)]
+#[csr(regs(stval = 0x143, vstval = 0x243))]
+pub struct Stval {
+    tval: BitField<Hex, 0, 63>,
+}
+
 /// Supervisor Counter-Enable Register
 #[derive(Csr)]
+#[csr(addr = 0x106, name = "scounteren")]
 pub struct Scounteren {
     hpm31: BitField<Bin, 31, 31>,
     hpm30: BitField<Bin, 30, 30>,
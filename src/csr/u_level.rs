@@ -0,0 +1,20 @@
+//! Definitions for the unprivileged CSRs
+use core::fmt::Display;
+use csr_macro::Csr;
+
+use super::Csr;
+use crate::bitfield::{BitField, Dec};
+
+/// Counter/Timer Register
+#[derive(Csr)]
+#[csr(regs(
+    cycle = 0xc00,
+    time = 0xc01,
+    instret = 0xc02,
+    mcycle = 0xb00,
+    minstret = 0xb02
+))]
+#[csr(regs(hpmcounter = 0xc03, mhpmcounter = 0xb03), first = 3, count = 29)]
+pub struct Counter {
+    count: BitField<Dec, 0, 63>,
+}
@@ -1,6 +1,56 @@
 //! Allow formatting of objects that implement the [BitFieldFormat](Csr) trait
 
-use std::fmt::Display;
+use core::fmt::Display;
+
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+use crate::bitfield::FieldValue;
+#[cfg(feature = "std")]
+use crate::bitfield::Decoded;
+
+/// Errors that may arise when setting fields
+#[cfg(feature = "std")]
+#[derive(Error, Debug)]
+pub enum FieldError {
+    #[error("\"{0}\" is not a field of {1}")]
+    Unknown(String, String),
+    #[error("\"{1}\" is not a valid value for field {0}")]
+    Value(String, String),
+    #[error("\"{0}\" is not a field assignment, expected FIELD=VALUE")]
+    Assignment(String),
+}
+
+/// A field's name, bit ranges `(START, END)` and decoder, as declared by its struct
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub bits: &'static [(usize, usize)],
+    /// The [BitFieldType](crate::bitfield::BitFieldType) decoding the field, e.g. `Priv`
+    pub kind: &'static str,
+}
+
+impl FieldInfo {
+    /// Get the lowest bit of the field in the raw value
+    pub fn start(&self) -> usize {
+        self.bits.iter().map(|&(s, _)| s).min().unwrap_or(0)
+    }
+
+    /// Get the highest bit of the field in the raw value
+    pub fn end(&self) -> usize {
+        self.bits.iter().map(|&(_, e)| e).max().unwrap_or(0)
+    }
+}
+
+/// A field's name, bit ranges `(START, END)`, decoder, value and decoded value
+#[cfg(feature = "std")]
+pub struct Field {
+    pub name: &'static str,
+    pub bits: &'static [(usize, usize)],
+    pub kind: &'static str,
+    pub value: u64,
+    pub decoded: Decoded,
+}
 
 /// Defines functions required for creating formattable fields
 pub trait Csr // TODO: rename to BitFieldFormat?
@@ -18,7 +68,129 @@ where
     //     Self: Sized;
 
     /// Get its name
-    fn name() -> String
-    where
-        Self: Sized;
+    fn name(&self) -> &'static str;
+
+    /// Set `field` of raw value `value` to `text`, written as a number or as the field is printed
+    #[cfg(feature = "std")]
+    fn set(&self, value: u64, field: &str, text: &str) -> Result<u64, FieldError>;
+
+    /// Get the layout of its fields in order of declaration
+    fn layout(&self) -> &'static [FieldInfo];
+
+    /// Get its field at `index` in order of declaration
+    fn field(&self, index: usize) -> Option<&dyn FieldValue>;
+
+    /// Get its fields in order of declaration
+    #[cfg(feature = "std")]
+    fn fields(&self) -> Fields<'_>;
+
+    /// Apply field assignments written as `field=value` to raw value `base`
+    ///
+    /// _Bits that are not covered by an assigned field keep their value from `base`_
+    #[cfg(feature = "std")]
+    fn encode(&self, base: u64, assignments: &[String]) -> Result<u64, FieldError> {
+        assignments.iter().try_fold(base, |value, assignment| {
+            let (field, text) = assignment
+                .split_once('=')
+                .ok_or_else(|| FieldError::Assignment(assignment.clone()))?;
+            self.set(value, field.trim(), text.trim())
+        })
+    }
+}
+
+/// Iterator over the [Field]s of a format, see [Csr::fields]
+#[cfg(feature = "std")]
+pub struct Fields<'a> {
+    csr: &'a dyn Csr,
+    index: usize,
+}
+
+#[cfg(feature = "std")]
+impl<'a> Fields<'a> {
+    /// Iterate over the fields of a format
+    pub fn new(csr: &'a dyn Csr) -> Self {
+        Fields { csr, index: 0 }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Iterator for Fields<'_> {
+    type Item = Field;
+
+    fn next(&mut self) -> Option<Field> {
+        let info = self.csr.layout().get(self.index)?;
+        let value = self.csr.field(self.index)?;
+        self.index += 1;
+        Some(Field {
+            name: info.name,
+            bits: info.bits,
+            kind: info.kind,
+            value: value.value(),
+            decoded: value.decode(),
+        })
+    }
+}
+
+/// Get the fields that changed between an old & new instance of the same format as `(old, new)`
+#[cfg(feature = "std")]
+pub fn diff(old: &dyn Csr, new: &dyn Csr) -> Vec<(Field, Field)> {
+    old.fields()
+        .zip(new.fields())
+        .filter(|(o, n)| o.value != n.value)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::csr::{Mstatus, PmpAddr, PmpCfg};
+    use crate::inst::SType;
+
+    #[test]
+    fn field_layout() {
+        assert_eq!(
+            Mstatus::FIELDS[7],
+            FieldInfo {
+                name: "mpp",
+                bits: &[(11, 12)],
+                kind: "Priv"
+            }
+        );
+        let imm = SType::FIELDS.iter().find(|f| f.name == "imm").unwrap();
+        assert_eq!(imm.bits, [(7, 11), (25, 31)]);
+        assert_eq!((imm.start(), imm.end()), (7, 31));
+
+        let csr: Box<dyn Csr> = Box::new(PmpAddr::new(0x1000));
+        let fields: Vec<_> = csr.fields().map(|f| (f.name, f.kind, f.value)).collect();
+        assert_eq!(fields[1], ("address", "RSh<2,Hex>", 0x1000));
+    }
+
+    #[test]
+    fn diff_changed_fields() {
+        let old = Mstatus::new(0x0000000a00001800);
+        let new = Mstatus::new(0x0000000a00000080);
+        let changed: Vec<_> = diff(&old, &new)
+            .into_iter()
+            .map(|(o, n)| (o.name, o.decoded.to_string(), n.decoded.to_string()))
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                ("mpie", "0b0".into(), "0b1".into()),
+                ("mpp", "Machine".into(), "User".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_assignments() {
+        let mstatus = Mstatus::new(0);
+        let assignments = ["mpp=M".into(), "mie=1".into(), "fs=dirty".into()];
+        assert_eq!(mstatus.encode(0, &assignments).unwrap(), 0x7808);
+        assert!(mstatus.encode(0, &["mpp".into()]).is_err());
+        assert!(mstatus.encode(0, &["foo=1".into()]).is_err());
+        let pmpcfg = PmpCfg::new(0);
+        let assignments = ["pmp0cfg=RWX NAPOT".into(), "pmp1cfg=R-X TOR Locked".into()];
+        assert_eq!(pmpcfg.encode(0, &assignments).unwrap(), 0x8d1f);
+    }
 }
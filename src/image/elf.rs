@@ -0,0 +1,130 @@
+//! Executable sections of little endian RISC-V ELF files
+
+use super::{Image, ImageError, Segment};
+
+/// ELF identification magic
+pub const MAGIC: &[u8] = b"\x7fELF";
+
+/// RISC-V machine type
+const EM_RISCV: u16 = 0xf3;
+/// Section type of program defined data
+const SHT_PROGBITS: u32 = 0x1;
+/// Section flag of executable sections
+const SHF_EXECINSTR: u64 = 0x4;
+
+/// Add an offset within the file, failing instead of overflowing
+fn add(offset: usize, n: usize) -> Result<usize, ImageError> {
+    offset
+        .checked_add(n)
+        .ok_or(ImageError::Elf("offset out of range"))
+}
+
+/// Read a little endian value of `size` bytes at `offset`
+fn read(data: &[u8], offset: usize, size: usize) -> Result<u64, ImageError> {
+    let bytes = data
+        .get(offset..add(offset, size)?)
+        .ok_or(ImageError::Elf("unexpected end of file"))?;
+    Ok(bytes.iter().rev().fold(0, |acc, b| acc << 8 | *b as u64))
+}
+
+/// Build an image from the executable sections of an ELF file
+pub fn parse(data: &[u8]) -> Result<Image, ImageError> {
+    // Word size dependent offsets of the file header & section header fields
+    let (word, shoff, shnum, sh_flags, sh_addr, sh_offset, sh_size) = match data.get(4) {
+        Some(1) => (4, 0x20, 0x30, 0x08, 0x0c, 0x10, 0x14),
+        Some(2) => (8, 0x28, 0x3c, 0x08, 0x10, 0x18, 0x20),
+        _ => return Err(ImageError::Elf("invalid class")),
+    };
+    if data.get(5) != Some(&1) {
+        return Err(ImageError::Elf("not little endian"));
+    }
+    if read(data, 0x12, 2)? as u16 != EM_RISCV {
+        return Err(ImageError::Elf("not a RISC-V file"));
+    }
+
+    let shoff = read(data, shoff, word)? as usize;
+    let shentsize = read(data, shnum - 2, 2)? as usize;
+    let shnum = read(data, shnum, 2)? as usize;
+
+    let mut image = Image::default();
+    for i in 0..shnum {
+        let header = i
+            .checked_mul(shentsize)
+            .ok_or(ImageError::Elf("offset out of range"))?;
+        let header = add(shoff, header)?;
+        let sh_type = read(data, add(header, 4)?, 4)? as u32;
+        let flags = read(data, add(header, sh_flags)?, word)?;
+        if sh_type != SHT_PROGBITS || flags & SHF_EXECINSTR == 0 {
+            continue;
+        }
+        let offset = read(data, add(header, sh_offset)?, word)? as usize;
+        let size = read(data, add(header, sh_size)?, word)? as usize;
+        let bytes = data
+            .get(offset..add(offset, size)?)
+            .ok_or(ImageError::Elf("section exceeds file"))?;
+        image.segments.push(Segment {
+            address: read(data, add(header, sh_addr)?, word)?,
+            bytes: bytes.to_vec(),
+        });
+    }
+    if image.segments.is_empty() {
+        return Err(ImageError::Elf("no executable sections"));
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An ELF64 file header with section headers at `shoff`
+    fn header(shoff: u64, shnum: u16) -> Vec<u8> {
+        let mut data = vec![0; 0x40];
+        data[..4].copy_from_slice(MAGIC);
+        data[4..6].copy_from_slice(&[2, 1]);
+        data[0x12..0x14].copy_from_slice(&EM_RISCV.to_le_bytes());
+        data[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        data[0x3a..0x3c].copy_from_slice(&0x40_u16.to_le_bytes());
+        data[0x3c..0x3e].copy_from_slice(&shnum.to_le_bytes());
+        data
+    }
+
+    /// A section header of an executable section at `offset` of `size` bytes
+    fn section(offset: u64, size: u64) -> Vec<u8> {
+        let mut data = vec![0; 0x40];
+        data[0x04..0x08].copy_from_slice(&SHT_PROGBITS.to_le_bytes());
+        data[0x08..0x10].copy_from_slice(&SHF_EXECINSTR.to_le_bytes());
+        data[0x18..0x20].copy_from_slice(&offset.to_le_bytes());
+        data[0x20..0x28].copy_from_slice(&size.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn executable_section() {
+        let mut data = header(0x40, 1);
+        data.extend(section(0x80, 4));
+        data.extend([0x13, 0x05, 0xc5, 0xff]);
+        let image = parse(&data).unwrap();
+        assert_eq!(image.segments[0].bytes, [0x13, 0x05, 0xc5, 0xff]);
+    }
+
+    #[test]
+    fn truncated_or_overflowing_headers_fail() {
+        assert!(matches!(
+            parse(&header(0x40, 1)[..0x30]),
+            Err(ImageError::Elf(_))
+        ));
+        assert!(matches!(parse(&header(0x40, 1)), Err(ImageError::Elf(_))));
+        assert!(matches!(
+            parse(&header(u64::MAX, 1)),
+            Err(ImageError::Elf(_))
+        ));
+        assert!(matches!(
+            parse(&header(0x40, 0xffff)),
+            Err(ImageError::Elf(_))
+        ));
+        let mut data = header(0x40, 1);
+        data.extend(section(u64::MAX, 2));
+        assert!(matches!(parse(&data), Err(ImageError::Elf(_))));
+    }
+}
@@ -0,0 +1,103 @@
+//! Hex dumps as produced by `objcopy -O ihex` or `objcopy -O verilog`
+
+use super::{Image, ImageError};
+
+/// Parse a hexadecimal byte string
+fn bytes(s: &str) -> Option<Vec<u8>> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parse an Intel hex dump
+pub fn parse_ihex(text: &str) -> Result<Image, ImageError> {
+    let mut image = Image::default();
+    let mut upper = 0;
+    for (n, line) in text.lines().enumerate().map(|(n, l)| (n + 1, l.trim())) {
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg| ImageError::Hex(n, msg);
+        let record = line
+            .strip_prefix(':')
+            .and_then(bytes)
+            .ok_or(err("expected a record"))?;
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            return Err(err("invalid record length"));
+        }
+        if record.iter().fold(0_u8, |acc, b| acc.wrapping_add(*b)) != 0 {
+            return Err(err("invalid checksum"));
+        }
+        let offset = (record[1] as u64) << 8 | record[2] as u64;
+        let data = &record[4..record.len() - 1];
+        match record[3] {
+            // Data
+            0x00 => {
+                for (i, byte) in data.iter().enumerate() {
+                    image.push(upper + offset + i as u64, *byte);
+                }
+            }
+            // End of file
+            0x01 => break,
+            // Extended segment & linear address
+            0x02 | 0x04 if data.len() == 2 => {
+                let shift = if record[3] == 0x02 { 4 } else { 16 };
+                upper = ((data[0] as u64) << 8 | data[1] as u64) << shift;
+            }
+            // Start segment & linear address
+            0x03 | 0x05 => (),
+            _ => return Err(err("unsupported record")),
+        }
+    }
+    Ok(image)
+}
+
+/// Parse a Verilog hex dump, i.e. `@address` followed by bytes
+pub fn parse_verilog(text: &str) -> Result<Image, ImageError> {
+    let mut image = Image::default();
+    let mut address = 0;
+    for (n, line) in text.lines().enumerate().map(|(n, l)| (n + 1, l)) {
+        for token in line.split_whitespace() {
+            if let Some(a) = token.strip_prefix('@') {
+                address = u64::from_str_radix(a, 16)
+                    .map_err(|_| ImageError::Hex(n, "invalid address"))?;
+                continue;
+            }
+            for byte in bytes(token).ok_or(ImageError::Hex(n, "invalid byte"))? {
+                image.push(address, byte);
+                address += 1;
+            }
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ihex_records() {
+        let text = ":020000040000FA\n:0400000013050000E4\n:02000800011FD6\n:00000001FF\n";
+        let image = parse_ihex(text).unwrap();
+        assert_eq!(image.segments.len(), 2);
+        assert_eq!(image.segments[0].address, 0x0);
+        assert_eq!(image.segments[0].bytes, vec![0x13, 0x05, 0x00, 0x00]);
+        assert_eq!(image.segments[1].address, 0x8);
+        assert_eq!(image.segments[1].bytes, vec![0x01, 0x1f]);
+    }
+
+    #[test]
+    fn ihex_bad_checksum_fails() {
+        assert!(parse_ihex(":0400000013050000E5\n").is_err());
+    }
+
+    #[test]
+    fn verilog_segments() {
+        let image = parse_verilog("@80000000\n13 05 00 00\n@80000010\n01 1f\n").unwrap();
+        assert_eq!(image.segments.len(), 2);
+        assert_eq!(image.segments[1].address, 0x80000010);
+        assert_eq!(image.segments[1].bytes, vec![0x01, 0x1f]);
+    }
+}
@@ -0,0 +1,77 @@
+//! Memory images loaded from raw binaries, hex dumps or ELF files
+
+use std::{fs, io};
+
+use thiserror::Error;
+
+mod elf;
+mod hex;
+
+/// Errors that may arise when loading a memory image
+#[derive(Error, Debug)]
+pub enum ImageError {
+    #[error("Could not read image: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed ELF file: {0}")]
+    Elf(&'static str),
+    #[error("Malformed hex dump on line {0}: {1}")]
+    Hex(usize, &'static str),
+}
+
+/// Contiguous bytes at an address
+pub struct Segment {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+}
+
+/// A memory image, i.e. a set of segments
+#[derive(Default)]
+pub struct Image {
+    pub segments: Vec<Segment>,
+}
+
+impl Image {
+    /// Append `byte` at `address`, extending the last segment if it ends at `address`
+    fn push(&mut self, address: u64, byte: u8) {
+        match self.segments.last_mut() {
+            Some(s) if s.address + s.bytes.len() as u64 == address => s.bytes.push(byte),
+            _ => self.segments.push(Segment {
+                address,
+                bytes: vec![byte],
+            }),
+        }
+    }
+
+    /// Get the parts of the segments within addresses `start..end`
+    pub fn range(&self, start: u64, end: u64) -> impl Iterator<Item = (u64, &[u8])> {
+        self.segments.iter().filter_map(move |s| {
+            let first = start.clamp(s.address, s.address + s.bytes.len() as u64);
+            let last = end.clamp(first, s.address + s.bytes.len() as u64);
+            let bytes = &s.bytes[(first - s.address) as usize..(last - s.address) as usize];
+            (!bytes.is_empty()).then_some((first, bytes))
+        })
+    }
+}
+
+/// Load a memory image from a file
+///
+/// The format is detected from the file's content:
+/// - ELF files: executable sections such as `.text`
+/// - Intel hex (`objcopy -O ihex`) & Verilog hex (`objcopy -O verilog`) dumps
+/// - Anything else is a raw binary located at address `base`
+pub fn load(path: &str, base: u64) -> Result<Image, ImageError> {
+    let data = fs::read(path)?;
+    if data.starts_with(elf::MAGIC) {
+        return elf::parse(&data);
+    }
+    match std::str::from_utf8(&data).map(str::trim_start) {
+        Ok(text) if text.starts_with(':') => hex::parse_ihex(text),
+        Ok(text) if text.starts_with('@') => hex::parse_verilog(text),
+        _ => Ok(Image {
+            segments: vec![Segment {
+                address: base,
+                bytes: data,
+            }],
+        }),
+    }
+}
@@ -0,0 +1,399 @@
+//! Instruction operands and how they are encoded in an instruction word
+
+use crate::bitfield::{get_bit, get_bits, parse_int, sext, FREG, XREG};
+use crate::{csr, encoding};
+
+/// Floating point rounding modes
+const RM: [&str; 8] = ["rne", "rtz", "rdn", "rup", "rmm", "rm5", "rm6", "dyn"];
+
+/// Location of a register index within an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reg {
+    /// Bits 11..7
+    Rd,
+    /// Bits 19..15
+    Rs1,
+    /// Bits 24..20
+    Rs2,
+    /// Bits 31..27
+    Rs3,
+    /// Bits 6..2 of a compressed instruction
+    CRs2,
+    /// Bits 4..2 of a compressed instruction (`rd'` or `rs2'`), encoding `x8`-`x15`
+    CRdP,
+    /// Bits 9..7 of a compressed instruction (`rs1'` or `rd'`), encoding `x8`-`x15`
+    CRs1P,
+    /// Implicit stack pointer of compressed instructions
+    Sp,
+}
+
+impl Reg {
+    /// Get the register index encoded in `inst`
+    pub fn index(&self, inst: u32) -> usize {
+        let index = match self {
+            Reg::Rd => get_bits(inst, 7, 11),
+            Reg::Rs1 => get_bits(inst, 15, 19),
+            Reg::Rs2 => get_bits(inst, 20, 24),
+            Reg::Rs3 => get_bits(inst, 27, 31),
+            Reg::CRs2 => get_bits(inst, 2, 6),
+            Reg::CRdP => get_bits(inst, 2, 4) + 8,
+            Reg::CRs1P => get_bits(inst, 7, 9) + 8,
+            Reg::Sp => 2,
+        };
+        index as usize
+    }
+
+    /// Get the bit range `(START, END)` of this location, if the register is encoded
+    pub fn bits(&self) -> Option<(usize, usize)> {
+        match self {
+            Reg::Rd => Some((7, 11)),
+            Reg::Rs1 => Some((15, 19)),
+            Reg::Rs2 => Some((20, 24)),
+            Reg::Rs3 => Some((27, 31)),
+            Reg::CRs2 => Some((2, 6)),
+            Reg::CRdP => Some((2, 4)),
+            Reg::CRs1P => Some((7, 9)),
+            Reg::Sp => None,
+        }
+    }
+
+    /// Encode register `index` at this location
+    pub fn insert(&self, index: usize) -> u32 {
+        let index = index as u32;
+        match self {
+            Reg::Rd => index << 7,
+            Reg::Rs1 => index << 15,
+            Reg::Rs2 => index << 20,
+            Reg::Rs3 => index << 27,
+            Reg::CRs2 => index << 2,
+            Reg::CRdP => (index - 8) << 2,
+            Reg::CRs1P => (index - 8) << 7,
+            Reg::Sp => 0,
+        }
+    }
+
+    /// Encode register `index` at this location if it can be encoded there
+    fn encode(&self, index: usize) -> Option<u32> {
+        let valid = match self {
+            Reg::CRdP | Reg::CRs1P => (8..16).contains(&index),
+            Reg::Sp => index == 2,
+            _ => index < 32,
+        };
+        valid.then(|| self.insert(index))
+    }
+}
+
+/// Location and interpretation of an immediate within an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Imm {
+    /// I-type immediate
+    I,
+    /// S-type immediate
+    S,
+    /// B-type branch offset
+    B,
+    /// U-type immediate
+    U,
+    /// J-type jump offset
+    J,
+    /// 6 bit shift amount
+    Shamt,
+    /// 5 bit shift amount
+    ShamtW,
+    /// 5 bit unsigned immediate for CSR instructions
+    Zimm,
+    /// CI-type signed immediate
+    C6,
+    /// CI-type shift amount
+    CShamt,
+    /// `c.lui` immediate
+    CLui,
+    /// `c.addi16sp` immediate
+    CAddi16sp,
+    /// `c.addi4spn` immediate
+    CAddi4spn,
+    /// `c.lw`/`c.sw` offset
+    CLw,
+    /// `c.ld`/`c.sd`/`c.fld`/`c.fsd` offset
+    CLd,
+    /// `c.lwsp` offset
+    CLwsp,
+    /// `c.ldsp`/`c.fldsp` offset
+    CLdsp,
+    /// `c.swsp` offset
+    CSwsp,
+    /// `c.sdsp`/`c.fsdsp` offset
+    CSdsp,
+    /// CJ-type jump offset
+    CJ,
+    /// CB-type branch offset
+    CB,
+}
+
+impl Imm {
+    /// Bit segments as `(first instruction bit, last instruction bit, first immediate bit)`
+    fn segments(&self) -> &'static [(usize, usize, usize)] {
+        match self {
+            Imm::I => &[(20, 31, 0)],
+            Imm::S => &[(7, 11, 0), (25, 31, 5)],
+            Imm::B => &[(8, 11, 1), (25, 30, 5), (7, 7, 11), (31, 31, 12)],
+            Imm::U => &[(12, 31, 0)],
+            Imm::J => &[(21, 30, 1), (20, 20, 11), (12, 19, 12), (31, 31, 20)],
+            Imm::Shamt => &[(20, 25, 0)],
+            Imm::ShamtW => &[(20, 24, 0)],
+            Imm::Zimm => &[(15, 19, 0)],
+            Imm::C6 | Imm::CShamt | Imm::CLui => &[(2, 6, 0), (12, 12, 5)],
+            Imm::CAddi16sp => &[(6, 6, 4), (2, 2, 5), (5, 5, 6), (3, 4, 7), (12, 12, 9)],
+            Imm::CAddi4spn => &[(6, 6, 2), (5, 5, 3), (11, 12, 4), (7, 10, 6)],
+            Imm::CLw => &[(6, 6, 2), (10, 12, 3), (5, 5, 6)],
+            Imm::CLd => &[(10, 12, 3), (5, 6, 6)],
+            Imm::CLwsp => &[(4, 6, 2), (12, 12, 5), (2, 3, 6)],
+            Imm::CLdsp => &[(5, 6, 3), (12, 12, 5), (2, 4, 6)],
+            Imm::CSwsp => &[(9, 12, 2), (7, 8, 6)],
+            Imm::CSdsp => &[(10, 12, 3), (7, 9, 6)],
+            Imm::CJ => &[
+                (3, 5, 1),
+                (11, 11, 4),
+                (2, 2, 5),
+                (7, 7, 6),
+                (6, 6, 7),
+                (9, 10, 8),
+                (8, 8, 10),
+                (12, 12, 11),
+            ],
+            Imm::CB => &[(3, 4, 1), (10, 11, 3), (2, 2, 5), (5, 6, 6), (12, 12, 8)],
+        }
+    }
+
+    /// Whether the immediate is sign extended
+    fn signed(&self) -> bool {
+        matches!(
+            self,
+            Imm::I
+                | Imm::S
+                | Imm::B
+                | Imm::J
+                | Imm::C6
+                | Imm::CLui
+                | Imm::CAddi16sp
+                | Imm::CJ
+                | Imm::CB
+        )
+    }
+
+    /// Get the immediate's value in `inst`
+    pub fn value(&self, inst: u32) -> i64 {
+        let segments = self.segments();
+        let value = segments.iter().fold(0, |acc, &(start, end, at)| {
+            acc | get_bits(inst, start, end) << at
+        });
+        if self.signed() {
+            let size = segments
+                .iter()
+                .map(|&(s, e, at)| at + e - s + 1)
+                .max()
+                .unwrap();
+            sext(value as u64, size)
+        } else {
+            value as i64
+        }
+    }
+
+    /// Encode immediate `value` at this location
+    ///
+    /// _Bits of `value` that do not fit are discarded_
+    pub fn insert(&self, value: i64) -> u32 {
+        self.segments().iter().fold(0, |acc, &(start, end, at)| {
+            let mask = (1 << (end - start + 1)) - 1;
+            acc | ((value >> at) as u32 & mask) << start
+        })
+    }
+
+    /// Encode immediate `value` at this location if it is representable, i.e. in range and aligned
+    fn encode(&self, value: i64) -> Option<u32> {
+        let value = match self {
+            // Accept the 20 bit hexadecimal form in which `c.lui` is printed
+            Imm::CLui if (0x80000..=0xfffff).contains(&value) => value - 0x100000,
+            _ => value,
+        };
+        let inst = self.insert(value);
+        (self.value(inst) == value).then_some(inst)
+    }
+
+    /// Format the immediate's value in `inst`
+    fn format(&self, inst: u32) -> String {
+        match self {
+            Imm::U => format!("0x{:x}", self.value(inst)),
+            Imm::CLui => format!("0x{:x}", self.value(inst) & 0xfffff),
+            _ => format!("{}", self.value(inst)),
+        }
+    }
+}
+
+/// An instruction operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arg {
+    /// Integer register
+    X(Reg),
+    /// Floating point register
+    F(Reg),
+    /// Immediate
+    Imm(Imm),
+    /// Memory operand with offset: `imm(reg)`
+    Mem(Imm, Reg),
+    /// Memory operand without offset: `(reg)`
+    Addr(Reg),
+    /// CSR address
+    Csr,
+    /// Fence predecessor set
+    Pred,
+    /// Fence successor set
+    Succ,
+    /// Floating point rounding mode
+    ///
+    /// _Omitted when dynamic_
+    Rm,
+    /// Acquire/release ordering bits
+    ///
+    /// _Appended to the mnemonic instead of printed as an operand_
+    Aqrl,
+}
+
+impl Arg {
+    /// Format the operand's value in instruction `inst`
+    ///
+    /// Returns `None` if the operand is not printed in the operand list
+    pub fn operand(&self, inst: u32) -> Option<String> {
+        let s = match self {
+            Arg::X(r) => XREG[r.index(inst)].to_string(),
+            Arg::F(r) => FREG[r.index(inst)].to_string(),
+            Arg::Imm(i) => i.format(inst),
+            Arg::Mem(i, r) => format!("{}({})", i.format(inst), XREG[r.index(inst)]),
+            Arg::Addr(r) => format!("({})", XREG[r.index(inst)]),
+            Arg::Csr => {
+                let address = get_bits(inst, 20, 31);
+                csr::name(address as u16).unwrap_or_else(|| format!("0x{:03x}", address))
+            }
+            Arg::Pred => fence_set(get_bits(inst, 24, 27)),
+            Arg::Succ => fence_set(get_bits(inst, 20, 23)),
+            Arg::Rm => match get_bits(inst, 12, 14) {
+                0b111 => return None,
+                rm => RM[rm as usize].to_string(),
+            },
+            Arg::Aqrl => return None,
+        };
+        Some(s)
+    }
+
+    /// Encode operand text `s` as this operand
+    ///
+    /// Returns `None` if `s` is not a valid value for this operand
+    pub fn encode(&self, s: &str) -> Option<u32> {
+        match self {
+            Arg::X(r) => r.encode(reg_index(&XREG, 'x', s)?),
+            Arg::F(r) => r.encode(reg_index(&FREG, 'f', s)?),
+            Arg::Imm(i) => i.encode(parse_int(s)?),
+            Arg::Mem(i, r) => {
+                let (imm, reg) = s.strip_suffix(')')?.split_once('(')?;
+                let imm = match imm.trim() {
+                    "" => 0,
+                    imm => parse_int(imm)?,
+                };
+                Some(i.encode(imm)? | Arg::X(*r).encode(reg.trim())?)
+            }
+            Arg::Addr(r) => {
+                let reg = s.strip_prefix('(')?.strip_suffix(')')?;
+                Arg::X(*r).encode(reg.trim())
+            }
+            Arg::Csr => {
+                let csr = match encoding::csr_address_map(s) {
+                    Some(address) => address as i64,
+                    None => parse_int(s)?,
+                };
+                (0..=0xfff).contains(&csr).then_some((csr as u32) << 20)
+            }
+            Arg::Pred => Some(parse_fence_set(s)? << 24),
+            Arg::Succ => Some(parse_fence_set(s)? << 20),
+            Arg::Rm => Some((RM.iter().position(|rm| *rm == s)? as u32) << 12),
+            Arg::Aqrl => None,
+        }
+    }
+
+    /// Get the register index of this operand in `inst`, if any
+    pub fn reg(&self, inst: u32) -> Option<usize> {
+        match self {
+            Arg::X(r) | Arg::F(r) | Arg::Mem(_, r) | Arg::Addr(r) => Some(r.index(inst)),
+            _ => None,
+        }
+    }
+
+    /// Get the immediate value of this operand in `inst`, if any
+    pub fn imm(&self, inst: u32) -> Option<i64> {
+        match self {
+            Arg::Imm(i) | Arg::Mem(i, _) => Some(i.value(inst)),
+            _ => None,
+        }
+    }
+
+    /// Encode the suffix `suffix` this operand appends to the mnemonic
+    ///
+    /// Returns `None` if the operand does not take `suffix`
+    pub fn encode_suffix(&self, suffix: &str) -> Option<u32> {
+        match (self, suffix) {
+            (Arg::Aqrl, ".aq") => Some(1 << 26),
+            (Arg::Aqrl, ".rl") => Some(1 << 25),
+            (Arg::Aqrl, ".aqrl") => Some(0b11 << 25),
+            _ => None,
+        }
+    }
+
+    /// Get the suffix this operand appends to the mnemonic of instruction `inst`
+    pub fn suffix(&self, inst: u32) -> &'static str {
+        match self {
+            Arg::Aqrl => match (get_bit(inst, 26), get_bit(inst, 25)) {
+                (0, 0) => "",
+                (1, 0) => ".aq",
+                (0, 1) => ".rl",
+                (_, _) => ".aqrl",
+            },
+            _ => "",
+        }
+    }
+}
+
+/// Parse a register name, either its ABI name or its index prefixed by `prefix`
+fn reg_index(names: &[&str; 32], prefix: char, s: &str) -> Option<usize> {
+    if let Some(i) = names.iter().position(|n| *n == s) {
+        return Some(i);
+    }
+    if prefix == 'x' && s == "fp" {
+        return Some(8);
+    }
+    s.strip_prefix(prefix)?.parse().ok().filter(|i| *i < 32)
+}
+
+/// Parse a fence predecessor/successor set
+fn parse_fence_set(s: &str) -> Option<u32> {
+    if s == "0" {
+        return Some(0);
+    }
+    s.chars().try_fold(0, |set, c| {
+        let bit = 0b1000 >> "iorw".find(c)?;
+        (set & bit == 0).then_some(set | bit)
+    })
+}
+
+/// Format a fence predecessor/successor set
+fn fence_set(set: u32) -> String {
+    let s: String = ["i", "o", "r", "w"]
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| set & (0b1000 >> i) != 0)
+        .map(|(_, c)| *c)
+        .collect();
+    if s.is_empty() {
+        "0".into()
+    } else {
+        s
+    }
+}
@@ -0,0 +1,84 @@
+//! Instruction encoding from assembly text, the inverse of decoding
+
+use thiserror::Error;
+
+use super::arg::Arg;
+use super::pseudo;
+use super::table::{custom, Def, COMPRESSED, INSTRUCTIONS};
+use super::Inst;
+
+/// Errors that may arise when assembling an instruction
+#[derive(Error, Debug)]
+pub enum AsmError {
+    #[error("\"{0}\" is not a supported instruction mnemonic")]
+    Unknown(String),
+    #[error("{0} takes {1} operands but {2} were given")]
+    OperandCount(&'static str, usize, usize),
+    #[error("\"{1}\" is not a valid operand for {0}")]
+    Operand(&'static str, String),
+    #[error("\"{0}\" assembles to 0x{1:x} which is reserved or decodes as another instruction")]
+    Mismatch(String, u32),
+}
+
+/// Assemble a single instruction from its assembly text, e.g. `addi a0, a1, -4`
+///
+/// Operands are written as printed by the decoder, the rounding mode of floating point
+/// instructions may be omitted to select dynamic rounding
+pub fn assemble(text: &str) -> Result<Inst, AsmError> {
+    let text = text.trim();
+    let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let operands: Vec<&str> = match operands.trim() {
+        "" => vec![],
+        o => o.split(',').map(str::trim).collect(),
+    };
+
+    // Replace pseudo-instruction by its base instruction
+    let (mnemonic, operands) = match pseudo::expand(mnemonic, &operands) {
+        Some((base, operands)) => (base, operands),
+        None => (mnemonic, operands.iter().map(|o| o.to_string()).collect()),
+    };
+
+    // Look up definition & encode suffixes
+    let (def, mut word) = lookup(mnemonic).ok_or(AsmError::Unknown(mnemonic.into()))?;
+    word |= def.mtch;
+
+    // Encode operands
+    let args: Vec<&Arg> = def.args.iter().filter(|a| **a != Arg::Aqrl).collect();
+    let args = match args.split_last() {
+        Some((Arg::Rm, rest)) if operands.len() == rest.len() => {
+            word |= Arg::Rm.encode("dyn").unwrap();
+            rest
+        }
+        _ => &args[..],
+    };
+    if args.len() != operands.len() {
+        return Err(AsmError::OperandCount(def.name, args.len(), operands.len()));
+    }
+    for (arg, operand) in args.iter().zip(&operands) {
+        word |= arg
+            .encode(operand)
+            .ok_or(AsmError::Operand(def.name, operand.clone()))?;
+    }
+
+    // Make sure the encoding agrees with the decoder
+    match Inst::new(word) {
+        Ok(inst) if inst.def.name == def.name => Ok(inst),
+        _ => Err(AsmError::Mismatch(text.into(), word)),
+    }
+}
+
+/// Find the definition of `mnemonic` and encode the suffixes appended to it
+fn lookup(mnemonic: &str) -> Option<(&'static Def, u32)> {
+    INSTRUCTIONS
+        .iter()
+        .chain(COMPRESSED)
+        .chain(custom())
+        .find_map(|def| {
+            let suffix = mnemonic.strip_prefix(def.name)?;
+            if suffix.is_empty() {
+                return Some((def, 0));
+            }
+            let bits = def.args.iter().find_map(|a| a.encode_suffix(suffix))?;
+            Some((def, bits))
+        })
+}
@@ -0,0 +1,77 @@
+//! Expansion of compressed (RVC) instructions into their 32 bit equivalent
+
+use super::arg::{Imm, Reg};
+use super::table::Def;
+use crate::encoding::*;
+
+/// Expand compressed instruction `inst` matching `def` into its 32 bit equivalent
+///
+/// Returns `None` for reserved encodings
+pub fn expand(def: &Def, inst: u32) -> Option<u32> {
+    let r = |n: usize| def.args[n].reg(inst).unwrap_or(0);
+    let i = |n: usize| def.args[n].imm(inst).unwrap_or(0);
+    let word = match def.name {
+        // Quadrant 0
+        "c.addi4spn" if i(2) != 0 => i_type(MATCH_ADDI, r(0), 2, i(2)),
+        "c.fld" => i_type(MATCH_FLD, r(0), r(1), i(1)),
+        "c.lw" => i_type(MATCH_LW, r(0), r(1), i(1)),
+        "c.ld" => i_type(MATCH_LD, r(0), r(1), i(1)),
+        "c.fsd" => s_type(MATCH_FSD, r(1), r(0), i(1)),
+        "c.sw" => s_type(MATCH_SW, r(1), r(0), i(1)),
+        "c.sd" => s_type(MATCH_SD, r(1), r(0), i(1)),
+        // Quadrant 1
+        "c.nop" => i_type(MATCH_ADDI, 0, 0, Imm::C6.value(inst)),
+        "c.addi" => i_type(MATCH_ADDI, r(0), r(0), i(1)),
+        "c.addiw" if r(0) != 0 => i_type(MATCH_ADDIW, r(0), r(0), i(1)),
+        "c.li" => i_type(MATCH_ADDI, r(0), 0, i(1)),
+        "c.addi16sp" if i(1) != 0 => i_type(MATCH_ADDI, 2, 2, i(1)),
+        "c.lui" if i(1) != 0 => MATCH_LUI | Reg::Rd.insert(r(0)) | Imm::U.insert(i(1)),
+        "c.srli" => i_type(MATCH_SRLI, r(0), r(0), i(1)),
+        "c.srai" => i_type(MATCH_SRAI, r(0), r(0), i(1)),
+        "c.andi" => i_type(MATCH_ANDI, r(0), r(0), i(1)),
+        "c.sub" => r_type(MATCH_SUB, r(0), r(0), r(1)),
+        "c.xor" => r_type(MATCH_XOR, r(0), r(0), r(1)),
+        "c.or" => r_type(MATCH_OR, r(0), r(0), r(1)),
+        "c.and" => r_type(MATCH_AND, r(0), r(0), r(1)),
+        "c.subw" => r_type(MATCH_SUBW, r(0), r(0), r(1)),
+        "c.addw" => r_type(MATCH_ADDW, r(0), r(0), r(1)),
+        "c.j" => MATCH_JAL | Imm::J.insert(i(0)),
+        "c.beqz" => b_type(MATCH_BEQ, r(0), 0, i(1)),
+        "c.bnez" => b_type(MATCH_BNE, r(0), 0, i(1)),
+        // Quadrant 2
+        "c.slli" => i_type(MATCH_SLLI, r(0), r(0), i(1)),
+        "c.fldsp" => i_type(MATCH_FLD, r(0), 2, i(1)),
+        "c.lwsp" if r(0) != 0 => i_type(MATCH_LW, r(0), 2, i(1)),
+        "c.ldsp" if r(0) != 0 => i_type(MATCH_LD, r(0), 2, i(1)),
+        "c.jr" if r(0) != 0 => i_type(MATCH_JALR, 0, r(0), 0),
+        "c.mv" => r_type(MATCH_ADD, r(0), 0, r(1)),
+        "c.ebreak" => MATCH_EBREAK,
+        "c.jalr" => i_type(MATCH_JALR, 1, r(0), 0),
+        "c.add" => r_type(MATCH_ADD, r(0), r(0), r(1)),
+        "c.fsdsp" => s_type(MATCH_FSD, 2, r(0), i(1)),
+        "c.swsp" => s_type(MATCH_SW, 2, r(0), i(1)),
+        "c.sdsp" => s_type(MATCH_SD, 2, r(0), i(1)),
+        _ => return None,
+    };
+    Some(word)
+}
+
+/// Build an R-type instruction
+fn r_type(mtch: u32, rd: usize, rs1: usize, rs2: usize) -> u32 {
+    mtch | Reg::Rd.insert(rd) | Reg::Rs1.insert(rs1) | Reg::Rs2.insert(rs2)
+}
+
+/// Build an I-type instruction
+fn i_type(mtch: u32, rd: usize, rs1: usize, imm: i64) -> u32 {
+    mtch | Reg::Rd.insert(rd) | Reg::Rs1.insert(rs1) | Imm::I.insert(imm)
+}
+
+/// Build an S-type instruction
+fn s_type(mtch: u32, rs1: usize, rs2: usize, imm: i64) -> u32 {
+    mtch | Reg::Rs1.insert(rs1) | Reg::Rs2.insert(rs2) | Imm::S.insert(imm)
+}
+
+/// Build a B-type instruction
+fn b_type(mtch: u32, rs1: usize, rs2: usize, imm: i64) -> u32 {
+    mtch | Reg::Rs1.insert(rs1) | Reg::Rs2.insert(rs2) | Imm::B.insert(imm)
+}
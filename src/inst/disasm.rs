@@ -0,0 +1,71 @@
+//! Disassembly of instruction streams mixing 16 and 32 bit instructions
+
+use std::fmt::Display;
+
+use super::{length, Inst, InstError};
+
+/// A disassembled instruction at some address
+pub struct Line {
+    pub address: u64,
+    /// Instruction word
+    pub word: u32,
+    /// Size in bytes
+    pub size: usize,
+    pub inst: Result<Inst, InstError>,
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let word = match self.size {
+            1 => format!("{:02x}      ", self.word),
+            2 => format!("{:04x}    ", self.word),
+            _ => format!("{:08x}", self.word),
+        };
+        let directive = match self.size {
+            1 => ".byte".to_string(),
+            n => format!(".{}byte", n),
+        };
+        write!(f, "{:8x}:\t{}\t", self.address, word)?;
+        match &self.inst {
+            Ok(inst) => write!(f, "{}", inst),
+            Err(_) => writeln!(f, "{} 0x{:0w$x}", directive, self.word, w = self.size * 2),
+        }
+    }
+}
+
+/// Disassemble little endian instruction stream `bytes` located at `address`
+///
+/// _Instructions that do not fit in the remaining bytes are disassembled as data_
+pub fn disassemble(bytes: &[u8], address: u64) -> impl Iterator<Item = Line> + '_ {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let parcel = |i: usize| Some(u16::from_le_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]));
+        let Some(low) = parcel(offset) else {
+            // A trailing odd byte can not hold an instruction
+            let byte = *bytes.get(offset)? as u32;
+            offset += 1;
+            return Some(Line {
+                address: address + offset as u64 - 1,
+                word: byte,
+                size: 1,
+                inst: Err(InstError::TooWide(byte as u64, 8)),
+            });
+        };
+        let low = low as u32;
+        let (word, size) = match (length(low), parcel(offset + 2)) {
+            (4, Some(high)) => (low | (high as u32) << 16, 4),
+            _ => (low, 2),
+        };
+        let line = Line {
+            address: address + offset as u64,
+            word,
+            size,
+            inst: match length(word) == size {
+                true => Inst::new(word),
+                false => Err(InstError::TooWide(word as u64, 16)),
+            },
+        };
+        offset += size;
+        Some(line)
+    })
+}
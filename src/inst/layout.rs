@@ -0,0 +1,487 @@
+//! Instruction format layouts
+
+use csr_macro::Csr;
+use std::fmt::{self, Display};
+
+use super::arg::Arg;
+use super::{length, Inst, InstError};
+use crate::bitfield::{
+    Bin, BitField, BitFieldType, Dec, Decoded, FReg, FRegC, FieldValue, Hex, MultiBitField, Opcode,
+    RSh, Seg, Signed, StyledWrite, XReg, XRegC,
+};
+use crate::format::{Csr, FieldError, FieldInfo, Fields};
+use crate::render::Render;
+
+/// R-type instruction
+#[derive(Csr)]
+pub struct RType {
+    opcode: BitField<Opcode, 0, 6>,
+    rd: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 12, 14>,
+    rs1: BitField<XReg, 15, 19>,
+    rs2: BitField<XReg, 20, 24>,
+    funct7: BitField<Bin, 25, 31>,
+}
+
+/// R4-type instruction
+#[derive(Csr)]
+pub struct R4Type {
+    opcode: BitField<Opcode, 0, 6>,
+    rd: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 12, 14>,
+    rs1: BitField<XReg, 15, 19>,
+    rs2: BitField<XReg, 20, 24>,
+    funct2: BitField<Bin, 25, 26>,
+    rs3: BitField<XReg, 27, 31>,
+}
+
+/// I-type instruction
+#[derive(Csr)]
+pub struct IType {
+    opcode: BitField<Opcode, 0, 6>,
+    rd: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 12, 14>,
+    rs1: BitField<XReg, 15, 19>,
+    imm: BitField<Signed, 20, 31>,
+}
+
+/// S-type instruction
+#[derive(Csr)]
+pub struct SType {
+    opcode: BitField<Opcode, 0, 6>,
+    funct3: BitField<Bin, 12, 14>,
+    rs1: BitField<XReg, 15, 19>,
+    rs2: BitField<XReg, 20, 24>,
+    imm: MultiBitField<Signed, (Seg<7, 11, 0>, Seg<25, 31, 5>)>,
+}
+
+/// B-type instruction
+#[derive(Csr)]
+pub struct BType {
+    opcode: BitField<Opcode, 0, 6>,
+    funct3: BitField<Bin, 12, 14>,
+    rs1: BitField<XReg, 15, 19>,
+    rs2: BitField<XReg, 20, 24>,
+    imm: MultiBitField<
+        Signed,
+        (
+            Seg<8, 11, 1>,
+            Seg<25, 30, 5>,
+            Seg<7, 7, 11>,
+            Seg<31, 31, 12>,
+        ),
+    >,
+}
+
+/// U-type instruction
+#[derive(Csr)]
+pub struct UType {
+    opcode: BitField<Opcode, 0, 6>,
+    rd: BitField<XReg, 7, 11>,
+    imm: BitField<RSh<12, Hex>, 12, 31>,
+}
+
+/// J-type instruction
+#[derive(Csr)]
+pub struct JType {
+    opcode: BitField<Opcode, 0, 6>,
+    rd: BitField<XReg, 7, 11>,
+    imm: MultiBitField<
+        Signed,
+        (
+            Seg<21, 30, 1>,
+            Seg<20, 20, 11>,
+            Seg<12, 19, 12>,
+            Seg<31, 31, 20>,
+        ),
+    >,
+}
+
+/// Compressed register instruction
+#[derive(Csr)]
+pub struct CrType {
+    op: BitField<Bin, 0, 1>,
+    rs2: BitField<XReg, 2, 6>,
+    rd_rs1: BitField<XReg, 7, 11>,
+    funct4: BitField<Bin, 12, 15>,
+}
+
+/// Compressed immediate instruction, e.g. `c.addi`
+#[derive(Csr)]
+pub struct CiType {
+    op: BitField<Bin, 0, 1>,
+    imm: MultiBitField<Signed, (Seg<2, 6, 0>, Seg<12, 12, 5>)>,
+    rd_rs1: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed shift by immediate instruction, i.e. `c.slli`
+#[derive(Csr)]
+#[csr(rename = "citype")]
+pub struct CiShamtType {
+    op: BitField<Bin, 0, 1>,
+    shamt: MultiBitField<Dec, (Seg<2, 6, 0>, Seg<12, 12, 5>)>,
+    rd_rs1: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed load upper immediate instruction, i.e. `c.lui`
+#[derive(Csr)]
+#[csr(rename = "citype")]
+pub struct CiLuiType {
+    op: BitField<Bin, 0, 1>,
+    imm: MultiBitField<Signed, (Seg<2, 6, 12>, Seg<12, 12, 17>)>,
+    rd_rs1: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed stack pointer adjustment instruction, i.e. `c.addi16sp`
+#[derive(Csr)]
+#[csr(rename = "citype")]
+pub struct CiSpType {
+    op: BitField<Bin, 0, 1>,
+    imm: MultiBitField<
+        Signed,
+        (
+            Seg<6, 6, 4>,
+            Seg<2, 2, 5>,
+            Seg<5, 5, 6>,
+            Seg<3, 4, 7>,
+            Seg<12, 12, 9>,
+        ),
+    >,
+    rd_rs1: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed stack-relative word load instruction, e.g. `c.lwsp`
+#[derive(Csr)]
+#[csr(rename = "citype")]
+pub struct CiLoadType {
+    op: BitField<Bin, 0, 1>,
+    offset: MultiBitField<Dec, (Seg<4, 6, 2>, Seg<12, 12, 5>, Seg<2, 3, 6>)>,
+    rd: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed stack-relative doubleword load instruction, e.g. `c.ldsp`
+#[derive(Csr)]
+#[csr(rename = "citype")]
+pub struct CiLoadDType {
+    op: BitField<Bin, 0, 1>,
+    offset: MultiBitField<Dec, (Seg<5, 6, 3>, Seg<12, 12, 5>, Seg<2, 4, 6>)>,
+    rd: BitField<XReg, 7, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed stack-relative word store instruction, e.g. `c.swsp`
+#[derive(Csr)]
+pub struct CssType {
+    op: BitField<Bin, 0, 1>,
+    rs2: BitField<XReg, 2, 6>,
+    offset: MultiBitField<Dec, (Seg<9, 12, 2>, Seg<7, 8, 6>)>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed stack-relative doubleword store instruction, e.g. `c.sdsp`
+#[derive(Csr)]
+#[csr(rename = "csstype")]
+pub struct CssDType {
+    op: BitField<Bin, 0, 1>,
+    rs2: BitField<XReg, 2, 6>,
+    offset: MultiBitField<Dec, (Seg<10, 12, 3>, Seg<7, 9, 6>)>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed wide immediate instruction
+#[derive(Csr)]
+pub struct CiwType {
+    op: BitField<Bin, 0, 1>,
+    rd: BitField<XRegC, 2, 4>,
+    imm: MultiBitField<Dec, (Seg<6, 6, 2>, Seg<5, 5, 3>, Seg<11, 12, 4>, Seg<7, 10, 6>)>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed word load instruction, e.g. `c.lw`
+#[derive(Csr)]
+pub struct ClType {
+    op: BitField<Bin, 0, 1>,
+    rd: BitField<XRegC, 2, 4>,
+    offset: MultiBitField<Dec, (Seg<6, 6, 2>, Seg<10, 12, 3>, Seg<5, 5, 6>)>,
+    rs1: BitField<XRegC, 7, 9>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed doubleword load instruction, e.g. `c.ld`
+#[derive(Csr)]
+#[csr(rename = "cltype")]
+pub struct ClDType {
+    op: BitField<Bin, 0, 1>,
+    rd: BitField<XRegC, 2, 4>,
+    offset: MultiBitField<Dec, (Seg<10, 12, 3>, Seg<5, 6, 6>)>,
+    rs1: BitField<XRegC, 7, 9>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed word store instruction, e.g. `c.sw`
+#[derive(Csr)]
+pub struct CsType {
+    op: BitField<Bin, 0, 1>,
+    rs2: BitField<XRegC, 2, 4>,
+    offset: MultiBitField<Dec, (Seg<6, 6, 2>, Seg<10, 12, 3>, Seg<5, 5, 6>)>,
+    rs1: BitField<XRegC, 7, 9>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed doubleword store instruction, e.g. `c.sd`
+#[derive(Csr)]
+#[csr(rename = "cstype")]
+pub struct CsDType {
+    op: BitField<Bin, 0, 1>,
+    rs2: BitField<XRegC, 2, 4>,
+    offset: MultiBitField<Dec, (Seg<10, 12, 3>, Seg<5, 6, 6>)>,
+    rs1: BitField<XRegC, 7, 9>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed arithmetic instruction
+#[derive(Csr)]
+pub struct CaType {
+    op: BitField<Bin, 0, 1>,
+    rs2: BitField<XRegC, 2, 4>,
+    funct2: BitField<Bin, 5, 6>,
+    rd_rs1: BitField<XRegC, 7, 9>,
+    funct6: BitField<Bin, 10, 15>,
+}
+
+/// Compressed branch instruction
+#[derive(Csr)]
+pub struct CbType {
+    op: BitField<Bin, 0, 1>,
+    offset: MultiBitField<
+        Signed,
+        (
+            Seg<3, 4, 1>,
+            Seg<10, 11, 3>,
+            Seg<2, 2, 5>,
+            Seg<5, 6, 6>,
+            Seg<12, 12, 8>,
+        ),
+    >,
+    rs1: BitField<XRegC, 7, 9>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed shift by immediate instruction, e.g. `c.srli`
+#[derive(Csr)]
+#[csr(rename = "cbtype")]
+pub struct CbShamtType {
+    op: BitField<Bin, 0, 1>,
+    shamt: MultiBitField<Dec, (Seg<2, 6, 0>, Seg<12, 12, 5>)>,
+    rd_rs1: BitField<XRegC, 7, 9>,
+    funct2: BitField<Bin, 10, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed AND immediate instruction, i.e. `c.andi`
+#[derive(Csr)]
+#[csr(rename = "cbtype")]
+pub struct CbImmType {
+    op: BitField<Bin, 0, 1>,
+    imm: MultiBitField<Signed, (Seg<2, 6, 0>, Seg<12, 12, 5>)>,
+    rd_rs1: BitField<XRegC, 7, 9>,
+    funct2: BitField<Bin, 10, 11>,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// Compressed jump instruction
+#[derive(Csr)]
+pub struct CjType {
+    op: BitField<Bin, 0, 1>,
+    // Segments are spelled out for the derive to read their bit ranges
+    #[allow(clippy::type_complexity)]
+    target: MultiBitField<
+        Signed,
+        (
+            Seg<3, 5, 1>,
+            Seg<11, 11, 4>,
+            Seg<2, 2, 5>,
+            Seg<7, 7, 6>,
+            Seg<6, 6, 7>,
+            Seg<9, 10, 8>,
+            Seg<8, 8, 10>,
+            Seg<12, 12, 11>,
+        ),
+    >,
+    funct3: BitField<Bin, 13, 15>,
+}
+
+/// A register field holding a floating point operand
+struct FRegField {
+    value: u64,
+    bits: &'static [(usize, usize)],
+}
+
+impl FieldValue for FRegField {
+    fn value(&self) -> u64 {
+        self.value
+    }
+
+    fn write(&self, out: &mut dyn StyledWrite) -> fmt::Result {
+        match self.bits {
+            // Compressed registers are encoded in 3 bits
+            [(start, end)] if end - start == 2 => FRegC::write(self.value, 3, out),
+            _ => FReg::write(self.value, 5, out),
+        }
+    }
+
+    fn decode(&self) -> Decoded {
+        let mut decoded = Decoded {
+            value: self.value,
+            bits: self.bits.to_vec(),
+            ..Default::default()
+        };
+        self.write(&mut decoded)
+            .expect("Decoding into memory should not fail");
+        decoded
+    }
+}
+
+/// An instruction format layout whose register fields are named after the floating point
+/// registers where the decoded instruction takes floating point operands
+struct Operands<L> {
+    layout: L,
+    /// Floating point register fields by index in the layout
+    fregs: Vec<Option<FRegField>>,
+}
+
+impl<L: Csr> Display for Operands<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Render::Plain.write_csr(self, f)
+    }
+}
+
+impl<L: Csr> Csr for Operands<L> {
+    fn new(value: u64) -> Self {
+        let layout = L::new(value);
+        let fp_bits: Vec<_> = match Inst::new(value as u32) {
+            Ok(inst) => inst
+                .def
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    Arg::F(reg) => reg.bits(),
+                    _ => None,
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let fregs = layout
+            .layout()
+            .iter()
+            .enumerate()
+            .map(|(i, info)| match info.bits {
+                [bits] if fp_bits.contains(bits) => Some(FRegField {
+                    value: layout.field(i)?.value(),
+                    bits: info.bits,
+                }),
+                _ => None,
+            })
+            .collect();
+        Operands { layout, fregs }
+    }
+
+    fn name(&self) -> &'static str {
+        self.layout.name()
+    }
+
+    fn set(&self, value: u64, field: &str, text: &str) -> Result<u64, FieldError> {
+        self.layout.set(value, field, text)
+    }
+
+    fn layout(&self) -> &'static [FieldInfo] {
+        self.layout.layout()
+    }
+
+    fn field(&self, index: usize) -> Option<&dyn FieldValue> {
+        match self.fregs.get(index) {
+            Some(Some(freg)) => Some(freg),
+            _ => self.layout.field(index),
+        }
+    }
+
+    fn fields(&self) -> Fields<'_> {
+        Fields::new(self)
+    }
+}
+
+/// Build an instruction format layout from an instruction's value
+///
+/// The format is selected by the major opcode, or quadrant and funct3 for compressed instructions.
+/// How a compressed immediate is scattered over the format and whether registers are named as
+/// integer or floating point registers depends on the decoded instruction.
+pub fn layout(value: u64) -> Result<Box<dyn Csr>, InstError> {
+    let word = u32::try_from(value).map_err(|_| InstError::TooWide(value, 32))?;
+    if length(word) == 2 {
+        if word > 0xffff {
+            return Err(InstError::TooWide(value, 16));
+        }
+        // Reserved encodings are shown with the format's most common immediate
+        let name = Inst::new(word).map_or("", |inst| inst.def.name);
+        return match (word & 0b11, word >> 13, (word >> 10) & 0b11) {
+            (0b00, 0b000, _) => Ok(Box::new(Operands::<CiwType>::new(value))),
+            (0b00, 0b001..=0b011, _) => match name {
+                "c.ld" | "c.fld" => Ok(Box::new(Operands::<ClDType>::new(value))),
+                _ => Ok(Box::new(Operands::<ClType>::new(value))),
+            },
+            (0b00, 0b101..=0b111, _) => match name {
+                "c.sd" | "c.fsd" => Ok(Box::new(Operands::<CsDType>::new(value))),
+                _ => Ok(Box::new(Operands::<CsType>::new(value))),
+            },
+            (0b01, 0b001, _) if name == "c.jal" => Ok(Box::new(Operands::<CjType>::new(value))),
+            (0b01, 0b011, _) => match name {
+                "c.addi16sp" => Ok(Box::new(Operands::<CiSpType>::new(value))),
+                "c.lui" => Ok(Box::new(Operands::<CiLuiType>::new(value))),
+                // Reserved, with a zero immediate
+                _ if (word >> 7) & 0x1f == 2 => Ok(Box::new(Operands::<CiSpType>::new(value))),
+                _ => Ok(Box::new(Operands::<CiLuiType>::new(value))),
+            },
+            (0b01, 0b000..=0b010, _) => Ok(Box::new(Operands::<CiType>::new(value))),
+            (0b01, 0b100, 0b11) => Ok(Box::new(Operands::<CaType>::new(value))),
+            (0b01, 0b100, 0b10) => Ok(Box::new(Operands::<CbImmType>::new(value))),
+            (0b01, 0b100, _) => Ok(Box::new(Operands::<CbShamtType>::new(value))),
+            (0b01, 0b101, _) => Ok(Box::new(Operands::<CjType>::new(value))),
+            (0b01, 0b110..=0b111, _) => Ok(Box::new(Operands::<CbType>::new(value))),
+            (0b10, 0b000, _) => Ok(Box::new(Operands::<CiShamtType>::new(value))),
+            (0b10, 0b001..=0b011, _) => match name {
+                "c.ldsp" | "c.fldsp" => Ok(Box::new(Operands::<CiLoadDType>::new(value))),
+                _ => Ok(Box::new(Operands::<CiLoadType>::new(value))),
+            },
+            (0b10, 0b100, _) => Ok(Box::new(Operands::<CrType>::new(value))),
+            (0b10, 0b101..=0b111, _) => match name {
+                "c.sdsp" | "c.fsdsp" => Ok(Box::new(Operands::<CssDType>::new(value))),
+                _ => Ok(Box::new(Operands::<CssType>::new(value))),
+            },
+            _ => Err(InstError::Unknown(word)),
+        };
+    }
+    match (word >> 2) & 0x1f {
+        // LOAD, LOAD-FP, MISC-MEM, OP-IMM, OP-IMM-32, JALR, SYSTEM
+        0x00 | 0x01 | 0x03 | 0x04 | 0x06 | 0x19 | 0x1c => {
+            Ok(Box::new(Operands::<IType>::new(value)))
+        }
+        // STORE, STORE-FP
+        0x08 | 0x09 => Ok(Box::new(Operands::<SType>::new(value))),
+        // AMO, OP, OP-32, OP-FP
+        0x0b | 0x0c | 0x0e | 0x14 => Ok(Box::new(Operands::<RType>::new(value))),
+        // MADD, MSUB, NMSUB, NMADD
+        0x10..=0x13 => Ok(Box::new(Operands::<R4Type>::new(value))),
+        // BRANCH
+        0x18 => Ok(Box::new(Operands::<BType>::new(value))),
+        // LUI, AUIPC
+        0x0d | 0x05 => Ok(Box::new(Operands::<UType>::new(value))),
+        // JAL
+        0x1b => Ok(Box::new(Operands::<JType>::new(value))),
+        _ => Err(InstError::Unknown(word)),
+    }
+}
@@ -0,0 +1,337 @@
+//! Instruction decoding based on the generated [encoding](crate::encoding) tables
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+mod arg;
+mod asm;
+mod compressed;
+mod disasm;
+mod layout;
+mod pseudo;
+mod table;
+pub use asm::assemble;
+pub use disasm::disassemble;
+pub use layout::*;
+pub use table::set_custom;
+use table::{custom, Def, COMPRESSED, INSTRUCTIONS};
+
+/// Errors that may arise when decoding an instruction
+#[derive(Error, Debug)]
+pub enum InstError {
+    #[error("0x{0:x} does not fit in a {1} bit instruction")]
+    TooWide(u64, usize),
+    #[error("0x{0:08x} is not an encoding of a supported instruction")]
+    Unknown(u32),
+    #[error("0x{0:04x} is a reserved compressed instruction encoding")]
+    Reserved(u32),
+    #[error("\"{0}\" is not a valid custom instruction: {1}")]
+    Custom(String, &'static str),
+    #[error("custom instructions are already set")]
+    CustomSet,
+}
+
+/// Get the length in bytes of the instruction starting with parcel `value`
+///
+/// _Only 16 and 32 bit instructions are supported_
+pub fn length(value: u32) -> usize {
+    if value & 0b11 == 0b11 {
+        4
+    } else {
+        2
+    }
+}
+
+/// A decoded instruction
+pub struct Inst {
+    def: &'static Def,
+    value: u32,
+    /// 32 bit equivalent of a compressed instruction
+    expansion: Option<u32>,
+}
+
+impl Inst {
+    /// Decode an instruction word
+    ///
+    /// If multiple definitions match, the one with the most fixed bits is chosen
+    pub fn new(value: u32) -> Result<Self, InstError> {
+        let table = match length(value) {
+            2 => COMPRESSED,
+            _ => INSTRUCTIONS,
+        };
+        let def = table
+            .iter()
+            .chain(custom())
+            .filter(|def| def.matches(value))
+            .max_by_key(|def| def.mask.count_ones())
+            .ok_or(InstError::Unknown(value))?;
+        let expansion = match length(value) {
+            2 => Some(compressed::expand(def, value).ok_or(InstError::Reserved(value))?),
+            _ => None,
+        };
+        Ok(Inst {
+            def,
+            value,
+            expansion,
+        })
+    }
+
+    /// Get the instruction word
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Get the 32 bit equivalent of a compressed instruction
+    pub fn expand(&self) -> Option<Inst> {
+        self.expansion.and_then(|e| Inst::new(e).ok())
+    }
+
+    /// Get the mnemonic, including suffixes such as `.aqrl`
+    pub fn mnemonic(&self) -> String {
+        let suffix: String = self.def.args.iter().map(|a| a.suffix(self.value)).collect();
+        format!("{}{}", self.def.name, suffix)
+    }
+
+    /// Get the formatted operands
+    pub fn operands(&self) -> Vec<String> {
+        self.def
+            .args
+            .iter()
+            .filter_map(|a| a.operand(self.value))
+            .collect()
+    }
+
+    /// Get the assembly text, i.e. mnemonic followed by operands
+    ///
+    /// _Uses the pseudo-instruction if there is one for this instruction_
+    pub fn text(&self) -> String {
+        let (mnemonic, operands) = match pseudo::find(&self.mnemonic(), &self.operands()) {
+            Some((name, operands)) => (name.to_string(), operands),
+            None => (self.mnemonic(), self.operands()),
+        };
+        if operands.is_empty() {
+            mnemonic
+        } else {
+            format!("{} {}", mnemonic, operands.join(", "))
+        }
+    }
+}
+
+impl Display for Inst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.expand() {
+            Some(e) => writeln!(f, "{} -> {}", self.text(), e.text()),
+            None => writeln!(f, "{}", self.text()),
+        }
+    }
+}
+
+/// Decode an instruction from its value
+///
+/// Values whose lowest two bits are not `0b11` are decoded as 16 bit compressed instructions
+pub fn format(value: u64) -> Result<Inst, InstError> {
+    let word = u32::try_from(value).map_err(|_| InstError::TooWide(value, 32))?;
+    if length(word) == 2 && word > 0xffff {
+        return Err(InstError::TooWide(value, 16));
+    }
+    Inst::new(word)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode(value: u64) -> String {
+        format(value).unwrap().to_string().trim_end().to_string()
+    }
+
+    #[test]
+    fn decode_r_type() {
+        assert_eq!(decode(0x00c58533), "add a0, a1, a2");
+        assert_eq!(decode(0x40b50533), "sub a0, a0, a1");
+    }
+
+    #[test]
+    fn decode_i_type() {
+        assert_eq!(decode(0xffc58513), "addi a0, a1, -4");
+        assert_eq!(decode(0x00813083), "ld ra, 8(sp)");
+        assert_eq!(decode(0x03f59513), "slli a0, a1, 63");
+    }
+
+    #[test]
+    fn decode_s_b_j_u_type() {
+        assert_eq!(decode(0xfe113c23), "sd ra, -8(sp)");
+        assert_eq!(decode(0xfeb50ee3), "beq a0, a1, -4");
+        assert_eq!(decode(0x008000ef), "jal ra, 8");
+        assert_eq!(decode(0x12345537), "lui a0, 0x12345");
+    }
+
+    #[test]
+    fn decode_pseudo() {
+        assert_eq!(decode(0x00000013), "nop");
+        assert_eq!(decode(0x00058513), "mv a0, a1");
+        assert_eq!(decode(0xfff00513), "li a0, -1");
+        assert_eq!(decode(0x00000513), "li a0, 0");
+        assert_eq!(decode(0x00008067), "ret");
+        assert_eq!(decode(0x00050067), "jr a0");
+        assert_eq!(decode(0xfe050ee3), "beqz a0, -4");
+        assert_eq!(decode(0x22b58553), "fmv.d fa0, fa1");
+    }
+
+    #[test]
+    fn decode_system() {
+        assert_eq!(decode(0x30200073), "mret");
+        assert_eq!(decode(0x30002573), "csrr a0, mstatus");
+        assert_eq!(decode(0x7c002573), "csrr a0, 0x7c0");
+        assert_eq!(decode(0x34159073), "csrw mepc, a1");
+        assert_eq!(decode(0x0ff0000f), "fence");
+        assert_eq!(decode(0x0230000f), "fence r, rw");
+        assert_eq!(decode(0x8330000f), "fence.tso");
+        assert_eq!(decode(0x0330000f), "fence rw, rw");
+    }
+
+    #[test]
+    fn decode_atomic_and_float() {
+        assert_eq!(decode(0x06b5352f), "amoadd.d.aqrl a0, a1, (a0)");
+        assert_eq!(decode(0x00c5f553), "fadd.s fa0, fa1, fa2");
+        assert_eq!(decode(0x00c59553), "fadd.s fa0, fa1, fa2, rtz");
+    }
+
+    #[test]
+    fn decode_hypervisor() {
+        assert_eq!(decode(0x6005c573), "hlv.b a0, (a1)");
+        assert_eq!(decode(0x6435c573), "hlvx.hu a0, (a1)");
+        assert_eq!(decode(0x62a5c073), "hsv.b a0, (a1)");
+        assert_eq!(decode(0x22b50073), "hfence.vvma a0, a1");
+        assert_eq!(decode(0x62000073), "hfence.gvma");
+        assert_eq!(decode(0x16050073), "sinval.vma a0");
+        assert_eq!(decode(0x18000073), "sfence.w.inval");
+        assert_eq!(decode(0x26b50073), "hinval.vvma a0, a1");
+    }
+
+    #[test]
+    fn decode_custom() {
+        assert!(matches!(
+            set_custom(&["bad 0x00000033 0x0000007f".into()]),
+            Err(InstError::Custom(..))
+        ));
+        set_custom(&["mac 0x0000000b 0xfe00707f rd rs1 rs2".into()]).unwrap();
+        assert_eq!(decode(0x00c5850b), "mac a0, a1, a2");
+        assert!(matches!(
+            set_custom(&["mac 0x0000000b 0xfe00707f rd rs1 rs2".into()]),
+            Err(InstError::CustomSet)
+        ));
+    }
+
+    #[test]
+    fn decode_compressed() {
+        assert_eq!(decode(0x1571), "c.addi a0, -4 -> addi a0, a0, -4");
+        assert_eq!(decode(0x8082), "c.jr ra -> ret");
+        assert_eq!(decode(0x852e), "c.mv a0, a1 -> mv a0, a1");
+        assert_eq!(decode(0x6500), "c.ld s0, 8(a0) -> ld s0, 8(a0)");
+        assert_eq!(decode(0xe406), "c.sdsp ra, 8(sp) -> sd ra, 8(sp)");
+        assert_eq!(decode(0x717d), "c.addi16sp sp, -16 -> addi sp, sp, -16");
+        assert_eq!(decode(0xbfe5), "c.j -8 -> j -8");
+        assert_eq!(decode(0xdd75), "c.beqz a0, -4 -> beqz a0, -4");
+        assert_eq!(decode(0x0028), "c.addi4spn a0, sp, 8 -> addi a0, sp, 8");
+        assert_eq!(decode(0x9002), "c.ebreak -> ebreak");
+    }
+
+    #[test]
+    fn compressed_layout_immediates() {
+        let field = |value: u64, name: &str| {
+            let layout = layout(value).unwrap();
+            let field = layout.fields().find(|f| f.name == name).unwrap();
+            field.decoded.to_string()
+        };
+        assert_eq!(field(0x1571, "imm"), "-4 (-0x4)"); // c.addi a0, -4
+        assert_eq!(field(0x757d, "imm"), "-4096 (-0x1000)"); // c.lui a0, 0xfffff
+        assert_eq!(field(0x717d, "imm"), "-16 (-0x10)"); // c.addi16sp sp, -16
+        assert_eq!(field(0x810d, "shamt"), "3"); // c.srli a0, 3
+        assert_eq!(field(0x41c8, "offset"), "4"); // c.lw a0, 4(a1)
+        assert_eq!(field(0x6500, "offset"), "8"); // c.ld s0, 8(a0)
+        assert_eq!(field(0xe406, "offset"), "8"); // c.sdsp ra, 8(sp)
+        assert_eq!(field(0xdd75, "offset"), "-4 (-0x4)"); // c.beqz a0, -4
+        assert_eq!(field(0xbfe5, "target"), "-8 (-0x8)"); // c.j -8
+        assert_eq!(layout(0x6500).unwrap().name(), "cltype");
+    }
+
+    #[test]
+    fn layout_register_files() {
+        let regs = |value: u64| {
+            let layout = layout(value).unwrap();
+            let names = ["rd", "rs1", "rs2", "rs3"];
+            layout
+                .fields()
+                .filter(|f| names.contains(&f.name))
+                .map(|f| f.decoded.to_string())
+                .collect::<Vec<_>>()
+        };
+        // fadd.s fa0, fa1, fa2
+        assert_eq!(regs(0x00c5f553), ["f10 (fa0)", "f11 (fa1)", "f12 (fa2)"]);
+        // feq.s a0, fa0, fa1
+        assert_eq!(regs(0xa0b52553), ["x10 (a0)", "f10 (fa0)", "f11 (fa1)"]);
+        // flw fa0, 4(a1)
+        assert_eq!(regs(0x0045a507), ["f10 (fa0)", "x11 (a1)"]);
+        // c.fld fa0, 8(a1)
+        assert_eq!(regs(0x2588), ["f10 (fa0)", "x11 (a1)"]);
+        // add a0, a1, a2
+        assert_eq!(regs(0x00c58533), ["x10 (a0)", "x11 (a1)", "x12 (a2)"]);
+    }
+
+    #[test]
+    fn assemble_round_trip() {
+        let words = [
+            0xffc58513, 0xfe113c23, 0xfeb50ee3, 0x008000ef, 0x12345537, 0x30002573, 0x0ff0000f,
+            0x06b5352f, 0x00c5f553, 0x00c59553, 0x6005c573, 0x1571, 0x6500, 0x717d, 0xbfe5,
+            0x00000013, 0x00058513, 0x00008067, 0x00050067, 0x34159073, 0x0230000f, 0x22b58553,
+        ];
+        for word in words {
+            let text = Inst::new(word).unwrap().text();
+            assert_eq!(assemble(&text).unwrap().value(), word, "{}", text);
+        }
+    }
+
+    #[test]
+    fn assemble_invalid_fails() {
+        assert!(assemble("addi a0, a1, 2048").is_err());
+        assert!(assemble("beq a0, a1, 3").is_err());
+        assert!(assemble("c.addi4spn a0, sp, 0").is_err());
+        assert!(assemble("c.ld a0, 8(a6)").is_err());
+        assert!(assemble("add a0, a1").is_err());
+        assert!(assemble("foo a0").is_err());
+    }
+
+    #[test]
+    fn disassemble_mixed_stream() {
+        let bytes = [
+            0x13, 0x05, 0xc5, 0xff, 0x71, 0x15, 0x82, 0x80, 0x00, 0x00, 0x13,
+        ];
+        let lines: Vec<_> = disassemble(&bytes, 0x8000_0000).collect();
+        let addresses: Vec<_> = lines.iter().map(|l| l.address).collect();
+        assert_eq!(
+            addresses,
+            [
+                0x8000_0000,
+                0x8000_0004,
+                0x8000_0006,
+                0x8000_0008,
+                0x8000_000a
+            ]
+        );
+        assert_eq!(lines[0].inst.as_ref().unwrap().text(), "addi a0, a0, -4");
+        assert_eq!(lines[1].inst.as_ref().unwrap().text(), "c.addi a0, -4");
+        assert_eq!(lines[2].inst.as_ref().unwrap().text(), "c.jr ra");
+        assert!(lines[3].inst.is_err());
+        assert_eq!(lines[4].size, 1);
+        assert!(lines[4].to_string().ends_with("\t.byte 0x13\n"));
+    }
+
+    #[test]
+    fn decode_unknown_fails() {
+        assert!(format(0x0000_0000).is_err());
+        assert!(format(0x1_0000_0033).is_err());
+        assert!(format(0x1_0001).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Pseudo-instructions, i.e. aliases of base instructions with some operands fixed
+
+/// Definition of a pseudo-instruction
+///
+/// Each of `operands` is the text of an operand of the base instruction where `$n` is replaced by
+/// operand `n` of the pseudo-instruction
+pub struct Pseudo {
+    /// Mnemonic of the pseudo-instruction
+    pub name: &'static str,
+    /// Mnemonic of the base instruction
+    pub base: &'static str,
+    /// Operands of the base instruction
+    pub operands: &'static [&'static str],
+}
+
+impl Pseudo {
+    const fn new(
+        name: &'static str,
+        base: &'static str,
+        operands: &'static [&'static str],
+    ) -> Self {
+        Pseudo {
+            name,
+            base,
+            operands,
+        }
+    }
+
+    /// Number of operands of the pseudo-instruction
+    fn arity(&self) -> usize {
+        self.operands
+            .iter()
+            .filter_map(|o| placeholder(o).map(|(_, n, _)| n + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Match the operands of base instruction `base`
+    ///
+    /// Returns the operands of the pseudo-instruction if they match
+    fn find(&self, base: &str, operands: &[String]) -> Option<Vec<String>> {
+        if base != self.base || operands.len() != self.operands.len() {
+            return None;
+        }
+        let mut args = vec![None; self.arity()];
+        for (template, operand) in self.operands.iter().zip(operands) {
+            match placeholder(template) {
+                None if template == operand => (),
+                None => return None,
+                Some((prefix, n, suffix)) => {
+                    let arg = operand.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                    match &args[n] {
+                        Some(a) if a != arg => return None,
+                        _ => args[n] = Some(arg.to_string()),
+                    }
+                }
+            }
+        }
+        args.into_iter().collect()
+    }
+
+    /// Build the operands of the base instruction from the pseudo-instruction's `args`
+    fn expand(&self, args: &[&str]) -> Vec<String> {
+        self.operands
+            .iter()
+            .map(|template| match placeholder(template) {
+                Some((prefix, n, suffix)) => format!("{}{}{}", prefix, args[n], suffix),
+                None => template.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Split an operand template in the text before, index of and text after its placeholder
+fn placeholder(template: &str) -> Option<(&str, usize, &str)> {
+    let (prefix, rest) = template.split_once('$')?;
+    let n = rest.chars().next()?.to_digit(10)? as usize;
+    Some((prefix, n, &rest[1..]))
+}
+
+/// Supported pseudo-instructions
+///
+/// _If several match the same instruction, the first one is used_
+const PSEUDO: &[Pseudo] = &[
+    Pseudo::new("nop", "addi", &["zero", "zero", "0"]),
+    Pseudo::new("li", "addi", &["$0", "zero", "$1"]),
+    Pseudo::new("mv", "addi", &["$0", "$1", "0"]),
+    Pseudo::new("mv", "add", &["$0", "zero", "$1"]),
+    Pseudo::new("not", "xori", &["$0", "$1", "-1"]),
+    Pseudo::new("neg", "sub", &["$0", "zero", "$1"]),
+    Pseudo::new("negw", "subw", &["$0", "zero", "$1"]),
+    Pseudo::new("sext.w", "addiw", &["$0", "$1", "0"]),
+    Pseudo::new("seqz", "sltiu", &["$0", "$1", "1"]),
+    Pseudo::new("snez", "sltu", &["$0", "zero", "$1"]),
+    Pseudo::new("sltz", "slt", &["$0", "$1", "zero"]),
+    Pseudo::new("sgtz", "slt", &["$0", "zero", "$1"]),
+    Pseudo::new("beqz", "beq", &["$0", "zero", "$1"]),
+    Pseudo::new("bnez", "bne", &["$0", "zero", "$1"]),
+    Pseudo::new("blez", "bge", &["zero", "$0", "$1"]),
+    Pseudo::new("bgez", "bge", &["$0", "zero", "$1"]),
+    Pseudo::new("bltz", "blt", &["$0", "zero", "$1"]),
+    Pseudo::new("bgtz", "blt", &["zero", "$0", "$1"]),
+    Pseudo::new("j", "jal", &["zero", "$0"]),
+    Pseudo::new("ret", "jalr", &["zero", "0(ra)"]),
+    Pseudo::new("jr", "jalr", &["zero", "0($0)"]),
+    Pseudo::new("fence", "fence", &["iorw", "iorw"]),
+    Pseudo::new("sfence.vma", "sfence.vma", &["zero", "zero"]),
+    Pseudo::new("sfence.vma", "sfence.vma", &["$0", "zero"]),
+    Pseudo::new("hfence.vvma", "hfence.vvma", &["zero", "zero"]),
+    Pseudo::new("hfence.vvma", "hfence.vvma", &["$0", "zero"]),
+    Pseudo::new("hfence.gvma", "hfence.gvma", &["zero", "zero"]),
+    Pseudo::new("hfence.gvma", "hfence.gvma", &["$0", "zero"]),
+    Pseudo::new("sinval.vma", "sinval.vma", &["zero", "zero"]),
+    Pseudo::new("sinval.vma", "sinval.vma", &["$0", "zero"]),
+    Pseudo::new("hinval.vvma", "hinval.vvma", &["zero", "zero"]),
+    Pseudo::new("hinval.vvma", "hinval.vvma", &["$0", "zero"]),
+    Pseudo::new("hinval.gvma", "hinval.gvma", &["zero", "zero"]),
+    Pseudo::new("hinval.gvma", "hinval.gvma", &["$0", "zero"]),
+    Pseudo::new("pause", "fence", &["w", "0"]),
+    Pseudo::new("csrr", "csrrs", &["$0", "$1", "zero"]),
+    Pseudo::new("csrw", "csrrw", &["zero", "$0", "$1"]),
+    Pseudo::new("csrs", "csrrs", &["zero", "$0", "$1"]),
+    Pseudo::new("csrc", "csrrc", &["zero", "$0", "$1"]),
+    Pseudo::new("csrwi", "csrrwi", &["zero", "$0", "$1"]),
+    Pseudo::new("csrsi", "csrrsi", &["zero", "$0", "$1"]),
+    Pseudo::new("csrci", "csrrci", &["zero", "$0", "$1"]),
+    Pseudo::new("fmv.s", "fsgnj.s", &["$0", "$1", "$1"]),
+    Pseudo::new("fneg.s", "fsgnjn.s", &["$0", "$1", "$1"]),
+    Pseudo::new("fabs.s", "fsgnjx.s", &["$0", "$1", "$1"]),
+    Pseudo::new("fmv.d", "fsgnj.d", &["$0", "$1", "$1"]),
+    Pseudo::new("fneg.d", "fsgnjn.d", &["$0", "$1", "$1"]),
+    Pseudo::new("fabs.d", "fsgnjx.d", &["$0", "$1", "$1"]),
+];
+
+/// Find the pseudo-instruction of base instruction `base` with `operands`
+///
+/// Returns the pseudo-instruction's mnemonic and operands
+pub fn find(base: &str, operands: &[String]) -> Option<(&'static str, Vec<String>)> {
+    PSEUDO
+        .iter()
+        .find_map(|p| Some((p.name, p.find(base, operands)?)))
+}
+
+/// Expand pseudo-instruction `name` with operands `args`
+///
+/// Returns the base instruction's mnemonic and operands
+pub fn expand(name: &str, args: &[&str]) -> Option<(&'static str, Vec<String>)> {
+    PSEUDO
+        .iter()
+        .find(|p| p.name == name && p.arity() == args.len())
+        .map(|p| (p.base, p.expand(args)))
+}
@@ -0,0 +1,810 @@
+//! Instruction definitions built from the generated [encoding](crate::encoding) tables
+
+use std::sync::OnceLock;
+
+use super::arg::{Arg, Arg::*, Imm::*, Reg::*};
+use super::InstError;
+use crate::encoding::*;
+
+/// Definition of an instruction: its mnemonic, encoding and operands
+pub struct Def {
+    /// Mnemonic
+    pub name: &'static str,
+    /// Value of the fixed bits
+    pub mtch: u32,
+    /// Mask selecting the fixed bits
+    pub mask: u32,
+    /// Operands in assembly order
+    pub args: &'static [Arg],
+}
+
+impl Def {
+    const fn new(name: &'static str, mtch: u32, mask: u32, args: &'static [Arg]) -> Self {
+        Def {
+            name,
+            mtch,
+            mask,
+            args,
+        }
+    }
+
+    /// Check whether `inst` is an encoding of this instruction
+    pub fn matches(&self, inst: u32) -> bool {
+        inst & self.mask == self.mtch
+    }
+}
+
+/// `fence.tso`, i.e. `fence rw, rw` with fence mode 0b1000, is a pseudo-op the encoding tables
+/// leave out
+const MATCH_FENCE_TSO: u32 = 0x8330000f;
+/// Mask of `fence.tso` covering the fence mode, predecessor & successor sets
+const MASK_FENCE_TSO: u32 = 0xfff0707f;
+
+/// Supported instructions
+pub const INSTRUCTIONS: &[Def] = &[
+    // RV32I/RV64I
+    Def::new("lui", MATCH_LUI, MASK_LUI, &[X(Rd), Imm(U)]),
+    Def::new("auipc", MATCH_AUIPC, MASK_AUIPC, &[X(Rd), Imm(U)]),
+    Def::new("jal", MATCH_JAL, MASK_JAL, &[X(Rd), Imm(J)]),
+    Def::new("jalr", MATCH_JALR, MASK_JALR, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("beq", MATCH_BEQ, MASK_BEQ, &[X(Rs1), X(Rs2), Imm(B)]),
+    Def::new("bne", MATCH_BNE, MASK_BNE, &[X(Rs1), X(Rs2), Imm(B)]),
+    Def::new("blt", MATCH_BLT, MASK_BLT, &[X(Rs1), X(Rs2), Imm(B)]),
+    Def::new("bge", MATCH_BGE, MASK_BGE, &[X(Rs1), X(Rs2), Imm(B)]),
+    Def::new("bltu", MATCH_BLTU, MASK_BLTU, &[X(Rs1), X(Rs2), Imm(B)]),
+    Def::new("bgeu", MATCH_BGEU, MASK_BGEU, &[X(Rs1), X(Rs2), Imm(B)]),
+    Def::new("lb", MATCH_LB, MASK_LB, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("lh", MATCH_LH, MASK_LH, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("lw", MATCH_LW, MASK_LW, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("ld", MATCH_LD, MASK_LD, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("lbu", MATCH_LBU, MASK_LBU, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("lhu", MATCH_LHU, MASK_LHU, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("lwu", MATCH_LWU, MASK_LWU, &[X(Rd), Mem(I, Rs1)]),
+    Def::new("sb", MATCH_SB, MASK_SB, &[X(Rs2), Mem(S, Rs1)]),
+    Def::new("sh", MATCH_SH, MASK_SH, &[X(Rs2), Mem(S, Rs1)]),
+    Def::new("sw", MATCH_SW, MASK_SW, &[X(Rs2), Mem(S, Rs1)]),
+    Def::new("sd", MATCH_SD, MASK_SD, &[X(Rs2), Mem(S, Rs1)]),
+    Def::new("addi", MATCH_ADDI, MASK_ADDI, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new("slti", MATCH_SLTI, MASK_SLTI, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new("sltiu", MATCH_SLTIU, MASK_SLTIU, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new("xori", MATCH_XORI, MASK_XORI, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new("ori", MATCH_ORI, MASK_ORI, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new("andi", MATCH_ANDI, MASK_ANDI, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new("slli", MATCH_SLLI, MASK_SLLI, &[X(Rd), X(Rs1), Imm(Shamt)]),
+    Def::new("srli", MATCH_SRLI, MASK_SRLI, &[X(Rd), X(Rs1), Imm(Shamt)]),
+    Def::new("srai", MATCH_SRAI, MASK_SRAI, &[X(Rd), X(Rs1), Imm(Shamt)]),
+    Def::new("add", MATCH_ADD, MASK_ADD, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("sub", MATCH_SUB, MASK_SUB, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("sll", MATCH_SLL, MASK_SLL, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("slt", MATCH_SLT, MASK_SLT, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("sltu", MATCH_SLTU, MASK_SLTU, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("xor", MATCH_XOR, MASK_XOR, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("srl", MATCH_SRL, MASK_SRL, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("sra", MATCH_SRA, MASK_SRA, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("or", MATCH_OR, MASK_OR, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("and", MATCH_AND, MASK_AND, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("addiw", MATCH_ADDIW, MASK_ADDIW, &[X(Rd), X(Rs1), Imm(I)]),
+    Def::new(
+        "slliw",
+        MATCH_SLLIW,
+        MASK_SLLIW,
+        &[X(Rd), X(Rs1), Imm(ShamtW)],
+    ),
+    Def::new(
+        "srliw",
+        MATCH_SRLIW,
+        MASK_SRLIW,
+        &[X(Rd), X(Rs1), Imm(ShamtW)],
+    ),
+    Def::new(
+        "sraiw",
+        MATCH_SRAIW,
+        MASK_SRAIW,
+        &[X(Rd), X(Rs1), Imm(ShamtW)],
+    ),
+    Def::new("addw", MATCH_ADDW, MASK_ADDW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("subw", MATCH_SUBW, MASK_SUBW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("sllw", MATCH_SLLW, MASK_SLLW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("srlw", MATCH_SRLW, MASK_SRLW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("sraw", MATCH_SRAW, MASK_SRAW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("fence", MATCH_FENCE, MASK_FENCE, &[Pred, Succ]),
+    Def::new("fence.tso", MATCH_FENCE_TSO, MASK_FENCE_TSO, &[]),
+    Def::new("ecall", MATCH_ECALL, MASK_ECALL, &[]),
+    Def::new("ebreak", MATCH_EBREAK, MASK_EBREAK, &[]),
+    // Zifencei
+    Def::new("fence.i", MATCH_FENCE_I, MASK_FENCE_I, &[]),
+    // Zicsr
+    Def::new("csrrw", MATCH_CSRRW, MASK_CSRRW, &[X(Rd), Csr, X(Rs1)]),
+    Def::new("csrrs", MATCH_CSRRS, MASK_CSRRS, &[X(Rd), Csr, X(Rs1)]),
+    Def::new("csrrc", MATCH_CSRRC, MASK_CSRRC, &[X(Rd), Csr, X(Rs1)]),
+    Def::new(
+        "csrrwi",
+        MATCH_CSRRWI,
+        MASK_CSRRWI,
+        &[X(Rd), Csr, Imm(Zimm)],
+    ),
+    Def::new(
+        "csrrsi",
+        MATCH_CSRRSI,
+        MASK_CSRRSI,
+        &[X(Rd), Csr, Imm(Zimm)],
+    ),
+    Def::new(
+        "csrrci",
+        MATCH_CSRRCI,
+        MASK_CSRRCI,
+        &[X(Rd), Csr, Imm(Zimm)],
+    ),
+    // RV32M/RV64M
+    Def::new("mul", MATCH_MUL, MASK_MUL, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("mulh", MATCH_MULH, MASK_MULH, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new(
+        "mulhsu",
+        MATCH_MULHSU,
+        MASK_MULHSU,
+        &[X(Rd), X(Rs1), X(Rs2)],
+    ),
+    Def::new("mulhu", MATCH_MULHU, MASK_MULHU, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("div", MATCH_DIV, MASK_DIV, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("divu", MATCH_DIVU, MASK_DIVU, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("rem", MATCH_REM, MASK_REM, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("remu", MATCH_REMU, MASK_REMU, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("mulw", MATCH_MULW, MASK_MULW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("divw", MATCH_DIVW, MASK_DIVW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("divuw", MATCH_DIVUW, MASK_DIVUW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("remw", MATCH_REMW, MASK_REMW, &[X(Rd), X(Rs1), X(Rs2)]),
+    Def::new("remuw", MATCH_REMUW, MASK_REMUW, &[X(Rd), X(Rs1), X(Rs2)]),
+    // RV32A/RV64A
+    Def::new("lr.w", MATCH_LR_W, MASK_LR_W, &[Aqrl, X(Rd), Addr(Rs1)]),
+    Def::new(
+        "sc.w",
+        MATCH_SC_W,
+        MASK_SC_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoswap.w",
+        MATCH_AMOSWAP_W,
+        MASK_AMOSWAP_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoadd.w",
+        MATCH_AMOADD_W,
+        MASK_AMOADD_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoxor.w",
+        MATCH_AMOXOR_W,
+        MASK_AMOXOR_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoand.w",
+        MATCH_AMOAND_W,
+        MASK_AMOAND_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoor.w",
+        MATCH_AMOOR_W,
+        MASK_AMOOR_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amomin.w",
+        MATCH_AMOMIN_W,
+        MASK_AMOMIN_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amomax.w",
+        MATCH_AMOMAX_W,
+        MASK_AMOMAX_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amominu.w",
+        MATCH_AMOMINU_W,
+        MASK_AMOMINU_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amomaxu.w",
+        MATCH_AMOMAXU_W,
+        MASK_AMOMAXU_W,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new("lr.d", MATCH_LR_D, MASK_LR_D, &[Aqrl, X(Rd), Addr(Rs1)]),
+    Def::new(
+        "sc.d",
+        MATCH_SC_D,
+        MASK_SC_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoswap.d",
+        MATCH_AMOSWAP_D,
+        MASK_AMOSWAP_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoadd.d",
+        MATCH_AMOADD_D,
+        MASK_AMOADD_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoxor.d",
+        MATCH_AMOXOR_D,
+        MASK_AMOXOR_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoand.d",
+        MATCH_AMOAND_D,
+        MASK_AMOAND_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amoor.d",
+        MATCH_AMOOR_D,
+        MASK_AMOOR_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amomin.d",
+        MATCH_AMOMIN_D,
+        MASK_AMOMIN_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amomax.d",
+        MATCH_AMOMAX_D,
+        MASK_AMOMAX_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amominu.d",
+        MATCH_AMOMINU_D,
+        MASK_AMOMINU_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    Def::new(
+        "amomaxu.d",
+        MATCH_AMOMAXU_D,
+        MASK_AMOMAXU_D,
+        &[Aqrl, X(Rd), X(Rs2), Addr(Rs1)],
+    ),
+    // RV32F/RV64F
+    Def::new("flw", MATCH_FLW, MASK_FLW, &[F(Rd), Mem(I, Rs1)]),
+    Def::new("fsw", MATCH_FSW, MASK_FSW, &[F(Rs2), Mem(S, Rs1)]),
+    Def::new(
+        "fmadd.s",
+        MATCH_FMADD_S,
+        MASK_FMADD_S,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fmsub.s",
+        MATCH_FMSUB_S,
+        MASK_FMSUB_S,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fnmsub.s",
+        MATCH_FNMSUB_S,
+        MASK_FNMSUB_S,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fnmadd.s",
+        MATCH_FNMADD_S,
+        MASK_FNMADD_S,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fadd.s",
+        MATCH_FADD_S,
+        MASK_FADD_S,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new(
+        "fsub.s",
+        MATCH_FSUB_S,
+        MASK_FSUB_S,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new(
+        "fmul.s",
+        MATCH_FMUL_S,
+        MASK_FMUL_S,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new(
+        "fdiv.s",
+        MATCH_FDIV_S,
+        MASK_FDIV_S,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new("fsqrt.s", MATCH_FSQRT_S, MASK_FSQRT_S, &[F(Rd), F(Rs1), Rm]),
+    Def::new(
+        "fsgnj.s",
+        MATCH_FSGNJ_S,
+        MASK_FSGNJ_S,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fsgnjn.s",
+        MATCH_FSGNJN_S,
+        MASK_FSGNJN_S,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fsgnjx.s",
+        MATCH_FSGNJX_S,
+        MASK_FSGNJX_S,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fmin.s",
+        MATCH_FMIN_S,
+        MASK_FMIN_S,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fmax.s",
+        MATCH_FMAX_S,
+        MASK_FMAX_S,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fcvt.w.s",
+        MATCH_FCVT_W_S,
+        MASK_FCVT_W_S,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.wu.s",
+        MATCH_FCVT_WU_S,
+        MASK_FCVT_WU_S,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.l.s",
+        MATCH_FCVT_L_S,
+        MASK_FCVT_L_S,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.lu.s",
+        MATCH_FCVT_LU_S,
+        MASK_FCVT_LU_S,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new("fmv.x.w", MATCH_FMV_X_W, MASK_FMV_X_W, &[X(Rd), F(Rs1)]),
+    Def::new("feq.s", MATCH_FEQ_S, MASK_FEQ_S, &[X(Rd), F(Rs1), F(Rs2)]),
+    Def::new("flt.s", MATCH_FLT_S, MASK_FLT_S, &[X(Rd), F(Rs1), F(Rs2)]),
+    Def::new("fle.s", MATCH_FLE_S, MASK_FLE_S, &[X(Rd), F(Rs1), F(Rs2)]),
+    Def::new("fclass.s", MATCH_FCLASS_S, MASK_FCLASS_S, &[X(Rd), F(Rs1)]),
+    Def::new(
+        "fcvt.s.w",
+        MATCH_FCVT_S_W,
+        MASK_FCVT_S_W,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.s.wu",
+        MATCH_FCVT_S_WU,
+        MASK_FCVT_S_WU,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.s.l",
+        MATCH_FCVT_S_L,
+        MASK_FCVT_S_L,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.s.lu",
+        MATCH_FCVT_S_LU,
+        MASK_FCVT_S_LU,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new("fmv.w.x", MATCH_FMV_W_X, MASK_FMV_W_X, &[F(Rd), X(Rs1)]),
+    // RV32D/RV64D
+    Def::new("fld", MATCH_FLD, MASK_FLD, &[F(Rd), Mem(I, Rs1)]),
+    Def::new("fsd", MATCH_FSD, MASK_FSD, &[F(Rs2), Mem(S, Rs1)]),
+    Def::new(
+        "fmadd.d",
+        MATCH_FMADD_D,
+        MASK_FMADD_D,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fmsub.d",
+        MATCH_FMSUB_D,
+        MASK_FMSUB_D,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fnmsub.d",
+        MATCH_FNMSUB_D,
+        MASK_FNMSUB_D,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fnmadd.d",
+        MATCH_FNMADD_D,
+        MASK_FNMADD_D,
+        &[F(Rd), F(Rs1), F(Rs2), F(Rs3), Rm],
+    ),
+    Def::new(
+        "fadd.d",
+        MATCH_FADD_D,
+        MASK_FADD_D,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new(
+        "fsub.d",
+        MATCH_FSUB_D,
+        MASK_FSUB_D,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new(
+        "fmul.d",
+        MATCH_FMUL_D,
+        MASK_FMUL_D,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new(
+        "fdiv.d",
+        MATCH_FDIV_D,
+        MASK_FDIV_D,
+        &[F(Rd), F(Rs1), F(Rs2), Rm],
+    ),
+    Def::new("fsqrt.d", MATCH_FSQRT_D, MASK_FSQRT_D, &[F(Rd), F(Rs1), Rm]),
+    Def::new(
+        "fsgnj.d",
+        MATCH_FSGNJ_D,
+        MASK_FSGNJ_D,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fsgnjn.d",
+        MATCH_FSGNJN_D,
+        MASK_FSGNJN_D,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fsgnjx.d",
+        MATCH_FSGNJX_D,
+        MASK_FSGNJX_D,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fmin.d",
+        MATCH_FMIN_D,
+        MASK_FMIN_D,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fmax.d",
+        MATCH_FMAX_D,
+        MASK_FMAX_D,
+        &[F(Rd), F(Rs1), F(Rs2)],
+    ),
+    Def::new(
+        "fcvt.s.d",
+        MATCH_FCVT_S_D,
+        MASK_FCVT_S_D,
+        &[F(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.d.s",
+        MATCH_FCVT_D_S,
+        MASK_FCVT_D_S,
+        &[F(Rd), F(Rs1), Rm],
+    ),
+    Def::new("feq.d", MATCH_FEQ_D, MASK_FEQ_D, &[X(Rd), F(Rs1), F(Rs2)]),
+    Def::new("flt.d", MATCH_FLT_D, MASK_FLT_D, &[X(Rd), F(Rs1), F(Rs2)]),
+    Def::new("fle.d", MATCH_FLE_D, MASK_FLE_D, &[X(Rd), F(Rs1), F(Rs2)]),
+    Def::new("fclass.d", MATCH_FCLASS_D, MASK_FCLASS_D, &[X(Rd), F(Rs1)]),
+    Def::new(
+        "fcvt.w.d",
+        MATCH_FCVT_W_D,
+        MASK_FCVT_W_D,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.wu.d",
+        MATCH_FCVT_WU_D,
+        MASK_FCVT_WU_D,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.l.d",
+        MATCH_FCVT_L_D,
+        MASK_FCVT_L_D,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.lu.d",
+        MATCH_FCVT_LU_D,
+        MASK_FCVT_LU_D,
+        &[X(Rd), F(Rs1), Rm],
+    ),
+    Def::new("fmv.x.d", MATCH_FMV_X_D, MASK_FMV_X_D, &[X(Rd), F(Rs1)]),
+    Def::new(
+        "fcvt.d.w",
+        MATCH_FCVT_D_W,
+        MASK_FCVT_D_W,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.d.wu",
+        MATCH_FCVT_D_WU,
+        MASK_FCVT_D_WU,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.d.l",
+        MATCH_FCVT_D_L,
+        MASK_FCVT_D_L,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new(
+        "fcvt.d.lu",
+        MATCH_FCVT_D_LU,
+        MASK_FCVT_D_LU,
+        &[F(Rd), X(Rs1), Rm],
+    ),
+    Def::new("fmv.d.x", MATCH_FMV_D_X, MASK_FMV_D_X, &[F(Rd), X(Rs1)]),
+    // Privileged
+    Def::new("sret", MATCH_SRET, MASK_SRET, &[]),
+    Def::new("mret", MATCH_MRET, MASK_MRET, &[]),
+    Def::new("wfi", MATCH_WFI, MASK_WFI, &[]),
+    Def::new(
+        "sfence.vma",
+        MATCH_SFENCE_VMA,
+        MASK_SFENCE_VMA,
+        &[X(Rs1), X(Rs2)],
+    ),
+    // Hypervisor
+    Def::new(
+        "hfence.vvma",
+        MATCH_HFENCE_VVMA,
+        MASK_HFENCE_VVMA,
+        &[X(Rs1), X(Rs2)],
+    ),
+    Def::new(
+        "hfence.gvma",
+        MATCH_HFENCE_GVMA,
+        MASK_HFENCE_GVMA,
+        &[X(Rs1), X(Rs2)],
+    ),
+    Def::new("hlv.b", MATCH_HLV_B, MASK_HLV_B, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlv.bu", MATCH_HLV_BU, MASK_HLV_BU, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlv.h", MATCH_HLV_H, MASK_HLV_H, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlv.hu", MATCH_HLV_HU, MASK_HLV_HU, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlvx.hu", MATCH_HLVX_HU, MASK_HLVX_HU, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlv.w", MATCH_HLV_W, MASK_HLV_W, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlv.wu", MATCH_HLV_WU, MASK_HLV_WU, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlvx.wu", MATCH_HLVX_WU, MASK_HLVX_WU, &[X(Rd), Addr(Rs1)]),
+    Def::new("hlv.d", MATCH_HLV_D, MASK_HLV_D, &[X(Rd), Addr(Rs1)]),
+    Def::new("hsv.b", MATCH_HSV_B, MASK_HSV_B, &[X(Rs2), Addr(Rs1)]),
+    Def::new("hsv.h", MATCH_HSV_H, MASK_HSV_H, &[X(Rs2), Addr(Rs1)]),
+    Def::new("hsv.w", MATCH_HSV_W, MASK_HSV_W, &[X(Rs2), Addr(Rs1)]),
+    Def::new("hsv.d", MATCH_HSV_D, MASK_HSV_D, &[X(Rs2), Addr(Rs1)]),
+    // Svinval
+    Def::new(
+        "sinval.vma",
+        MATCH_SINVAL_VMA,
+        MASK_SINVAL_VMA,
+        &[X(Rs1), X(Rs2)],
+    ),
+    Def::new(
+        "sfence.w.inval",
+        MATCH_SFENCE_W_INVAL,
+        MASK_SFENCE_W_INVAL,
+        &[],
+    ),
+    Def::new(
+        "sfence.inval.ir",
+        MATCH_SFENCE_INVAL_IR,
+        MASK_SFENCE_INVAL_IR,
+        &[],
+    ),
+    Def::new(
+        "hinval.vvma",
+        MATCH_HINVAL_VVMA,
+        MASK_HINVAL_VVMA,
+        &[X(Rs1), X(Rs2)],
+    ),
+    Def::new(
+        "hinval.gvma",
+        MATCH_HINVAL_GVMA,
+        MASK_HINVAL_GVMA,
+        &[X(Rs1), X(Rs2)],
+    ),
+];
+
+/// User supplied instructions in the custom-0..3 opcode space
+static CUSTOM: OnceLock<Vec<Def>> = OnceLock::new();
+
+/// Get the user supplied custom instructions
+pub fn custom() -> &'static [Def] {
+    CUSTOM.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Set the user supplied custom instructions
+///
+/// Each definition is written as `NAME MATCH MASK [OPERAND...]` where operands are named as in
+/// the opcodes submodule, e.g. `rd rs1 rs2` or `rd rs1 imm12`
+///
+/// _Custom instructions can only be set once, later calls fail with [InstError::CustomSet]_
+pub fn set_custom(defs: &[String]) -> Result<(), InstError> {
+    // Definitions live as long as the program, don't leak them on calls that can't succeed
+    if CUSTOM.get().is_some() {
+        return Err(InstError::CustomSet);
+    }
+    let defs = defs
+        .iter()
+        .map(|d| parse_custom(d))
+        .collect::<Result<_, _>>()?;
+    CUSTOM.set(defs).map_err(|_| InstError::CustomSet)
+}
+
+/// Parse a custom instruction definition
+fn parse_custom(def: &str) -> Result<Def, InstError> {
+    let err = |msg| InstError::Custom(def.to_string(), msg);
+    let mut fields = def.split_whitespace();
+    let name = fields.next().ok_or(err("missing name"))?;
+    let mut int = || {
+        let s = fields.next().ok_or(err("missing MATCH or MASK"))?;
+        let s = s
+            .strip_prefix("0x")
+            .ok_or(err("MATCH and MASK must be hexadecimal"))?;
+        u32::from_str_radix(s, 16).map_err(|_| err("MATCH and MASK must be 32 bit values"))
+    };
+    let (mtch, mask) = (int()?, int()?);
+    if !matches!(mtch & 0x7f, 0x0b | 0x2b | 0x5b | 0x7b) || mask & 0x7f != 0x7f {
+        return Err(err("not in custom-0..3 opcode space"));
+    }
+    if mtch & !mask != 0 {
+        return Err(err("MATCH has bits outside of MASK"));
+    }
+    let args = fields
+        .filter_map(|a| match a {
+            "rd" => Some(Ok(X(Rd))),
+            "rs1" => Some(Ok(X(Rs1))),
+            "rs2" => Some(Ok(X(Rs2))),
+            "rs3" => Some(Ok(X(Rs3))),
+            "imm12" => Some(Ok(Imm(I))),
+            "imm12hi" => Some(Ok(Imm(S))),
+            "bimm12hi" => Some(Ok(Imm(B))),
+            "imm20" => Some(Ok(Imm(U))),
+            "jimm20" => Some(Ok(Imm(J))),
+            "shamtd" => Some(Ok(Imm(Shamt))),
+            "shamtw" => Some(Ok(Imm(ShamtW))),
+            "zimm" => Some(Ok(Imm(Zimm))),
+            "csr" => Some(Ok(Csr)),
+            "rm" => Some(Ok(Rm)),
+            "aqrl" => Some(Ok(Aqrl)),
+            // Low halves are covered by their high half
+            "imm12lo" | "bimm12lo" => None,
+            _ => Some(Err(err("unknown operand"))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Def::new(
+        Box::leak(name.into()),
+        mtch,
+        mask,
+        Box::leak(args.into_boxed_slice()),
+    ))
+}
+
+/// Supported compressed instructions
+pub const COMPRESSED: &[Def] = &[
+    // Quadrant 0
+    Def::new(
+        "c.addi4spn",
+        MATCH_C_ADDI4SPN,
+        MASK_C_ADDI4SPN,
+        &[X(CRdP), X(Sp), Imm(CAddi4spn)],
+    ),
+    Def::new(
+        "c.fld",
+        MATCH_C_FLD,
+        MASK_C_FLD,
+        &[F(CRdP), Mem(CLd, CRs1P)],
+    ),
+    Def::new("c.lw", MATCH_C_LW, MASK_C_LW, &[X(CRdP), Mem(CLw, CRs1P)]),
+    Def::new("c.ld", MATCH_C_LD, MASK_C_LD, &[X(CRdP), Mem(CLd, CRs1P)]),
+    Def::new(
+        "c.fsd",
+        MATCH_C_FSD,
+        MASK_C_FSD,
+        &[F(CRdP), Mem(CLd, CRs1P)],
+    ),
+    Def::new("c.sw", MATCH_C_SW, MASK_C_SW, &[X(CRdP), Mem(CLw, CRs1P)]),
+    Def::new("c.sd", MATCH_C_SD, MASK_C_SD, &[X(CRdP), Mem(CLd, CRs1P)]),
+    // Quadrant 1
+    Def::new("c.nop", MATCH_C_NOP, MASK_C_NOP, &[]),
+    Def::new("c.addi", MATCH_C_ADDI, MASK_C_ADDI, &[X(Rd), Imm(C6)]),
+    Def::new("c.addiw", MATCH_C_ADDIW, MASK_C_ADDIW, &[X(Rd), Imm(C6)]),
+    Def::new("c.li", MATCH_C_LI, MASK_C_LI, &[X(Rd), Imm(C6)]),
+    Def::new(
+        "c.addi16sp",
+        MATCH_C_ADDI16SP,
+        MASK_C_ADDI16SP,
+        &[X(Sp), Imm(CAddi16sp)],
+    ),
+    Def::new("c.lui", MATCH_C_LUI, MASK_C_LUI, &[X(Rd), Imm(CLui)]),
+    Def::new(
+        "c.srli",
+        MATCH_C_SRLI,
+        MASK_C_SRLI,
+        &[X(CRs1P), Imm(CShamt)],
+    ),
+    Def::new(
+        "c.srai",
+        MATCH_C_SRAI,
+        MASK_C_SRAI,
+        &[X(CRs1P), Imm(CShamt)],
+    ),
+    Def::new("c.andi", MATCH_C_ANDI, MASK_C_ANDI, &[X(CRs1P), Imm(C6)]),
+    Def::new("c.sub", MATCH_C_SUB, MASK_C_SUB, &[X(CRs1P), X(CRdP)]),
+    Def::new("c.xor", MATCH_C_XOR, MASK_C_XOR, &[X(CRs1P), X(CRdP)]),
+    Def::new("c.or", MATCH_C_OR, MASK_C_OR, &[X(CRs1P), X(CRdP)]),
+    Def::new("c.and", MATCH_C_AND, MASK_C_AND, &[X(CRs1P), X(CRdP)]),
+    Def::new("c.subw", MATCH_C_SUBW, MASK_C_SUBW, &[X(CRs1P), X(CRdP)]),
+    Def::new("c.addw", MATCH_C_ADDW, MASK_C_ADDW, &[X(CRs1P), X(CRdP)]),
+    Def::new("c.j", MATCH_C_J, MASK_C_J, &[Imm(CJ)]),
+    Def::new("c.beqz", MATCH_C_BEQZ, MASK_C_BEQZ, &[X(CRs1P), Imm(CB)]),
+    Def::new("c.bnez", MATCH_C_BNEZ, MASK_C_BNEZ, &[X(CRs1P), Imm(CB)]),
+    // Quadrant 2
+    Def::new("c.slli", MATCH_C_SLLI, MASK_C_SLLI, &[X(Rd), Imm(CShamt)]),
+    Def::new(
+        "c.fldsp",
+        MATCH_C_FLDSP,
+        MASK_C_FLDSP,
+        &[F(Rd), Mem(CLdsp, Sp)],
+    ),
+    Def::new(
+        "c.lwsp",
+        MATCH_C_LWSP,
+        MASK_C_LWSP,
+        &[X(Rd), Mem(CLwsp, Sp)],
+    ),
+    Def::new(
+        "c.ldsp",
+        MATCH_C_LDSP,
+        MASK_C_LDSP,
+        &[X(Rd), Mem(CLdsp, Sp)],
+    ),
+    Def::new("c.jr", MATCH_C_JR, MASK_C_JR, &[X(Rd)]),
+    Def::new("c.mv", MATCH_C_MV, MASK_C_MV, &[X(Rd), X(CRs2)]),
+    Def::new("c.ebreak", MATCH_C_EBREAK, MASK_C_EBREAK, &[]),
+    Def::new("c.jalr", MATCH_C_JALR, MASK_C_JALR, &[X(Rd)]),
+    Def::new("c.add", MATCH_C_ADD, MASK_C_ADD, &[X(Rd), X(CRs2)]),
+    Def::new(
+        "c.fsdsp",
+        MATCH_C_FSDSP,
+        MASK_C_FSDSP,
+        &[F(CRs2), Mem(CSdsp, Sp)],
+    ),
+    Def::new(
+        "c.swsp",
+        MATCH_C_SWSP,
+        MASK_C_SWSP,
+        &[X(CRs2), Mem(CSwsp, Sp)],
+    ),
+    Def::new(
+        "c.sdsp",
+        MATCH_C_SDSP,
+        MASK_C_SDSP,
+        &[X(CRs2), Mem(CSdsp, Sp)],
+    ),
+];
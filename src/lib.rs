@@ -0,0 +1,24 @@
+//! Decoding of RISC-V CSRs, virtual memory related formats and instructions into their fields
+//!
+//! CSRs are built by name or address with [csr::format], which also yields the name the CSR is
+//! registered under, and virtual memory related formats such as page table entries with
+//! [vmem::format], both yield a [format::Csr] whose fields are [bitfield::BitField]s
+//!
+//! Without the default `std` feature only the CSR & vmem structs, their bitfields and
+//! [render::Render::write_csr] & [render::Render::write_named] remain, which write into a [core::fmt::Write] without allocating
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod bitfield;
+#[cfg(feature = "std")]
+mod encoding;
+pub mod format;
+pub mod render;
+
+pub mod csr;
+#[cfg(feature = "std")]
+pub mod image;
+#[cfg(feature = "std")]
+pub mod inst;
+#[cfg(feature = "std")]
+pub mod log;
+pub mod vmem;
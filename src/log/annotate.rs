@@ -0,0 +1,40 @@
+//! Values printed in ad-hoc formats such as `mcause=0x8` or `satp: 0x8000000000080400`
+
+use super::{is_csr, pairs};
+use crate::vmem;
+
+/// Find the `NAME=0x...` and `NAME: 0x...` values in a log line
+///
+/// _Only names of CSRs and virtual memory related formats are considered_
+pub fn values(line: &str) -> Vec<(String, u64)> {
+    pairs(line, false)
+        .into_iter()
+        .filter(|(name, _)| known(name))
+        .collect()
+}
+
+/// Check whether `name` is the name of a CSR or virtual memory related format
+fn known(name: &str) -> bool {
+    is_csr(name) || vmem::format(name, 0).is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_values() {
+        assert_eq!(
+            values("[trap] mcause=0x8000000000000007 MEPC: 0x80000a2c foo=0x1"),
+            [
+                ("mcause".to_string(), 0x8000000000000007),
+                ("mepc".to_string(), 0x80000a2c)
+            ]
+        );
+        assert_eq!(
+            values("pte_sv39 = 0x2000040f, time: 12"),
+            [("pte_sv39".to_string(), 0x2000040f)]
+        );
+        assert!(values("satp: 0xzz mstatus=").is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+//! Crash dumps of the Linux kernel (oops) and OpenSBI (trap error or panic)
+
+use std::fmt::Display;
+
+use super::pairs;
+use crate::bitfield::Decoded;
+use crate::csr;
+use crate::format::Csr;
+use crate::render::Render;
+
+/// How a crash dump names the trap's registers
+struct Dialect {
+    /// Software that prints the dump
+    source: &'static str,
+    /// Names of the exception PC, trap cause & trap value
+    pc: &'static str,
+    cause: &'static str,
+    tval: &'static str,
+    /// Names in the dump & the CSRs they hold, in the order of the dump's register table
+    csrs: &'static [(&'static str, &'static str)],
+}
+
+/// Linux oops register block
+const LINUX: Dialect = Dialect {
+    source: "Linux oops",
+    pc: "epc",
+    cause: "cause",
+    tval: "badaddr",
+    csrs: &[
+        ("epc", "sepc"),
+        ("status", "sstatus"),
+        ("cause", "scause"),
+        ("badaddr", "stval"),
+    ],
+};
+
+/// OpenSBI trap dump
+const OPENSBI: Dialect = Dialect {
+    source: "OpenSBI trap",
+    pc: "mepc",
+    cause: "mcause",
+    tval: "mtval",
+    csrs: &[
+        ("mepc", "mepc"),
+        ("mstatus", "mstatus"),
+        ("mcause", "mcause"),
+        ("mtval", "mtval"),
+        ("mtval2", "mtval2"),
+        ("mtinst", "mtinst"),
+    ],
+};
+
+/// A crash dump
+pub struct Crash {
+    /// Software that printed the dump
+    pub source: &'static str,
+    /// Decoded CSRs & their names in the order of the source's register table
+    pub csrs: Vec<(&'static str, Box<dyn Csr>)>,
+    /// Trap cause, previous privilege, exception PC, trap value & return address if present in
+    /// the dump
+    cause: Option<Decoded>,
+    privilege: Option<Decoded>,
+    pc: Option<u64>,
+    tval: Option<u64>,
+    ra: Option<u64>,
+}
+
+/// Find a Linux oops or OpenSBI trap dump in `text` and decode its CSRs
+///
+/// _The first dump found is used if there are several_
+pub fn crash(text: &str, xlen: usize) -> Option<Crash> {
+    let values: Vec<(String, u64)> = text.lines().flat_map(|l| pairs(l, true)).collect();
+    let get = |name: &str| values.iter().find(|(n, _)| n == name).map(|(_, v)| *v);
+    let dialect = [LINUX, OPENSBI]
+        .into_iter()
+        .find(|d| get(d.cause).is_some())?;
+    let csrs: Vec<(&'static str, Box<dyn Csr>)> = dialect
+        .csrs
+        .iter()
+        .filter_map(|(name, csr)| csr::format(csr, get(name)?, xlen).ok())
+        .collect();
+    // Fields are searched in order of the given names, e.g. `mpp` takes precedence over `spp`
+    let field = |names: &[&str]| {
+        let fields: Vec<_> = csrs.iter().flat_map(|(_, c)| c.fields()).collect();
+        let mut found = names
+            .iter()
+            .filter_map(|n| fields.iter().find(|f| f.name == *n));
+        found.next().map(|f| f.decoded.clone())
+    };
+    Some(Crash {
+        source: dialect.source,
+        cause: field(&["code"]),
+        privilege: field(&["mpp", "spp"]),
+        pc: get(dialect.pc),
+        tval: get(dialect.tval),
+        ra: get("ra"),
+        csrs,
+    })
+}
+
+impl Crash {
+    /// Explain the crash in one line
+    pub fn render(&self, render: Render) -> String {
+        let mut s = format!("{}: ", self.source);
+        match &self.cause {
+            Some(cause) => s += &render.decoded(cause),
+            None => s += "Unknown cause",
+        }
+        if let Some(pc) = self.pc {
+            s += &format!(" at 0x{:x}", pc);
+        }
+        if let Some(privilege) = &self.privilege {
+            s += &format!(" in {} mode", render.decoded(privilege));
+        }
+        if let Some(ra) = self.ra {
+            s += &format!(", called from 0x{:x}", ra);
+        }
+        match self.tval {
+            Some(tval) if tval != 0 => s + &format!(", trap value 0x{:x}", tval),
+            _ => s,
+        }
+    }
+}
+
+impl Display for Crash {
+    /// Explain the crash in one line
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(Render::Plain))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linux_oops() {
+        let dump = "epc : ffffffff8000a1b2 ra : ffffffff8000a1a0 sp : ff20000000013d30\n \
+                    t5 : 0000000000000000 t6 : 0000000000000000\n\
+                    status: 0000000200000120 badaddr: 0000000000000008 cause: 000000000000000d\n";
+        let crash = crash(dump, 64).unwrap();
+        assert_eq!(crash.csrs.len(), 4);
+        assert_eq!(crash.csrs[3].0, "stval");
+        assert_eq!(
+            crash.to_string(),
+            "Linux oops: Load page fault at 0xffffffff8000a1b2 in Supervisor mode, \
+             called from 0xffffffff8000a1a0, trap value 0x8"
+        );
+    }
+
+    #[test]
+    fn opensbi_trap() {
+        let dump = "sbi_trap_error: hart0: trap handler failed (error -2)\n\
+                    sbi_trap_error: hart0: mcause=0x0000000000000002 mtval=0x0000000000000000\n\
+                    sbi_trap_error: hart0: mtval2=0x0000000000000000 mtinst=0x0000000000000000\n\
+                    sbi_trap_error: hart0: mepc=0x0000000080000000 mstatus=0x0000000a00000800\n";
+        let crash = crash(dump, 64).unwrap();
+        assert_eq!(crash.csrs.len(), 6);
+        assert_eq!(
+            crash.to_string(),
+            "OpenSBI trap: Illegal instruction at 0x80000000 in Supervisor mode"
+        );
+        assert!(super::crash("nothing to see", 64).is_none());
+    }
+}
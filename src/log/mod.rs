@@ -0,0 +1,51 @@
+//! Extraction of CSR values from logs and register dumps
+
+mod annotate;
+mod crash;
+mod regs;
+mod trace;
+pub use annotate::values;
+pub use crash::crash;
+pub use regs::registers;
+pub use trace::timeline;
+
+use crate::encoding;
+
+/// Find the `NAME=VALUE` and `NAME: VALUE` pairs with a hexadecimal value in a line
+///
+/// Values must be `0x` prefixed unless `bare` is set, names are converted to lowercase
+fn pairs(line: &str, bare: bool) -> Vec<(String, u64)> {
+    let mut pairs = Vec::new();
+    for (i, _) in line.match_indices(['=', ':']) {
+        let before = line[..i].trim_end();
+        let start = before
+            .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+            .len();
+        let after = line[i + 1..].trim_start();
+        let digits = match after.strip_prefix("0x") {
+            Some(digits) => digits,
+            None if bare => after,
+            None => continue,
+        };
+        let end = digits
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(digits.len());
+        let name = &before[start..];
+        match u64::from_str_radix(&digits[..end], 16) {
+            Ok(value) if !name.is_empty() => pairs.push((name.to_lowercase(), value)),
+            _ => continue,
+        }
+    }
+    pairs
+}
+
+/// Parse a hexadecimal value with or without a `0x` prefix
+fn parse_hex(token: &str) -> Option<u64> {
+    let digits = token.strip_prefix("0x").unwrap_or(token);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Check whether `name` is the name of a CSR
+fn is_csr(name: &str) -> bool {
+    encoding::csr_address_map(name).is_some()
+}
@@ -0,0 +1,56 @@
+//! Register dumps such as QEMU's monitor `info registers` or GDB's `info all-registers`
+
+use super::{is_csr, parse_hex};
+
+/// Find the CSR values in a register dump
+///
+/// Each CSR name must be followed by its hexadecimal value, registers are listed in the order they
+/// first appear and a later value of the same CSR replaces an earlier one
+pub fn registers(text: &str) -> Vec<(String, u64)> {
+    let mut regs: Vec<(String, u64)> = Vec::new();
+    for line in text.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        for pair in tokens.windows(2) {
+            let name = pair[0].to_lowercase();
+            let Some(value) = parse_hex(pair[1]).filter(|_| is_csr(&name)) else {
+                continue;
+            };
+            match regs.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, v)) => *v = value,
+                None => regs.push((name, value)),
+            }
+        }
+    }
+    regs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn qemu_info_registers() {
+        let dump = " pc       0000000080000a2c\n \
+                    mhartid  0000000000000000\n \
+                    mstatus  0000000a00001800\n \
+                    mcause   8000000000000007\n \
+                    x0/zero  0000000000000000 x1/ra    0000000080000010\n";
+        assert_eq!(
+            registers(dump),
+            [
+                ("mhartid".to_string(), 0),
+                ("mstatus".to_string(), 0xa00001800),
+                ("mcause".to_string(), 0x8000000000000007),
+            ]
+        );
+    }
+
+    #[test]
+    fn gdb_info_all_registers() {
+        let dump = "pc             0x80000a2c       0x80000a2c <trap+12>\n\
+                    satp           0x8000000000080400       -9223372036854250496\n\
+                    satp           0x0      0\n\
+                    priv           0x3      prv:3 [Machine]\n";
+        assert_eq!(registers(dump), [("satp".to_string(), 0)]);
+    }
+}
@@ -0,0 +1,152 @@
+//! Simulation traces from Spike's `--log-commits` and QEMU's `-d int`
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+
+use super::{pairs, parse_hex};
+use crate::csr;
+use crate::format::{self, Csr, Field};
+use crate::render::Render;
+
+/// A CSR write in a trace and the fields it changed
+pub struct Event {
+    /// Line number in the trace, starting at 1
+    pub line: usize,
+    /// Name of the written CSR
+    pub name: String,
+    /// Changed fields with their previous value, which is `None` for the first write of a CSR
+    pub changes: Vec<(Option<Field>, Field)>,
+}
+
+impl Event {
+    /// Render the write as its line & CSR followed by a line per changed field
+    pub fn render(&self, render: Render) -> String {
+        let mut s = format!("{}: {}\n", self.line, self.name);
+        for (old, new) in &self.changes {
+            let new_value = render.decoded(&new.decoded);
+            let _ = match old {
+                Some(old) => writeln!(
+                    s,
+                    "\t{}: {} \u{2192} {}",
+                    new.name,
+                    render.decoded(&old.decoded),
+                    new_value
+                ),
+                None => writeln!(s, "\t{}: {}", new.name, new_value),
+            };
+        }
+        s
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(Render::Plain))
+    }
+}
+
+/// Find the CSR writes on a trace line
+///
+/// Spike records them as `c<ADDR>_<NAME> <VALUE>`, QEMU trap lines such as
+/// `riscv_cpu_do_interrupt: hart:0, async:0, cause:0000000000000002, epc:0x..., tval:0x...` are
+/// taken as writes to the machine mode trap CSRs since they do not tell which mode takes the trap
+fn writes(line: &str, xlen: usize) -> Vec<(String, u64)> {
+    if line.contains("riscv_cpu_do_interrupt") {
+        let values = pairs(line, true);
+        let get = |name: &str| values.iter().find(|(n, _)| n == name).map(|(_, v)| *v);
+        // Unsupported XLENs fail when the CSRs are formatted, don't overflow the shift before that
+        let interrupt = get("async")
+            .unwrap_or(0)
+            .checked_shl(xlen.saturating_sub(1) as u32)
+            .unwrap_or(0);
+        let csrs = [
+            ("mcause", get("cause").map(|c| c | interrupt)),
+            ("mepc", get("epc")),
+            ("mtval", get("tval")),
+        ];
+        return csrs
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value?)))
+            .collect();
+    }
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    tokens
+        .windows(2)
+        .filter_map(|pair| {
+            let (addr, name) = pair[0].strip_prefix('c')?.split_once('_')?;
+            addr.parse::<u16>().ok()?;
+            Some((name.to_string(), parse_hex(pair[1])?))
+        })
+        .collect()
+}
+
+/// Decode every CSR write in a trace, keeping only the fields changed since the previous write of
+/// the same CSR
+///
+/// _Writes that do not change any field and CSRs without a format are left out_
+pub fn timeline(text: &str, xlen: usize) -> Vec<Event> {
+    let mut last: HashMap<String, Box<dyn Csr>> = HashMap::new();
+    let mut events = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        for (name, value) in writes(line, xlen) {
+            let Ok((_, csr)) = csr::format(&name, value, xlen) else {
+                continue;
+            };
+            let changes: Vec<_> = match last.get(&name) {
+                Some(old) => format::diff(&**old, &*csr)
+                    .into_iter()
+                    .map(|(o, n)| (Some(o), n))
+                    .collect(),
+                None => csr.fields().map(|n| (None, n)).collect(),
+            };
+            if !changes.is_empty() {
+                events.push(Event {
+                    line: i + 1,
+                    name: name.clone(),
+                    changes,
+                });
+            }
+            last.insert(name, csr);
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spike_commits() {
+        let trace = "core   0: 3 0x80000004 (0x30529073) c773_mtvec 0x0000000080000010\n\
+                     core   0: 3 0x80000008 (0x30046073) c768_mstatus 0x0000000a00000008\n\
+                     core   0: 3 0x8000000c (0x30046073) c768_mstatus 0x0000000a00000008\n\
+                     core   0: 3 0x80000010 (0x30047073) c768_mstatus 0x0000000a00000000 x5 0x1\n";
+        let events = timeline(trace, 64);
+        let summary: Vec<_> = events.iter().map(|e| (e.line, e.changes.len())).collect();
+        assert_eq!(summary, [(1, 2), (2, 23), (4, 1)]);
+        assert_eq!(
+            events[2].to_string(),
+            "4: mstatus\n\tmie: 0b1 \u{2192} 0b0\n"
+        );
+        let satp = timeline("core   0: 3 0x8 (0x1) c384_satp 0xf000000000000000\n", 64);
+        assert!(satp[0]
+            .render(Render::Ansi)
+            .contains("mode: \x1b[33mInvalid address translation mode (0b1111)\x1b[0m"));
+    }
+
+    #[test]
+    fn qemu_interrupts() {
+        let trace = "riscv_cpu_do_interrupt: hart:0, async:1, cause:0000000000000007, \
+                     epc:0x0000000080000a2c, tval:0x0000000000000000, desc=m_timer\n";
+        let events = timeline(trace, 64);
+        let names: Vec<_> = events.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["mcause", "mepc", "mtval"]);
+        assert_eq!(
+            events[0].changes[1].1.decoded.to_string(),
+            "Machine timer interrupt"
+        );
+        assert!(timeline(trace, 0).is_empty());
+        assert!(timeline(trace, 128).is_empty());
+    }
+}
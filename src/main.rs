@@ -1,57 +1,523 @@
+use std::env;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal};
 use std::process::exit;
+use std::sync::OnceLock;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use clap_num::maybe_hex;
-
-mod bitfield;
-mod encoding;
-mod format;
-
-mod csr;
-mod inst;
-mod vmem;
+use rv_format::render::{self, Render};
+use rv_format::{csr, format, image, inst, log, vmem};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     /// CSR
-    name: String,
+    #[clap(required_unless_present = "batch")]
+    name: Option<String>,
 
-    /// Value
-    #[clap(value_parser=maybe_hex::<u64>)]
-    value: u64,
+    /// Value, assembly text for `asm`, file for `dis`, `regs`, `annotate`, `crash` & `trace`
+    /// (`-` or none for stdin) or CSR for `encode` & `diff`
+    value: Option<String>,
+
+    /// Field assignments `FIELD=VALUE` for `encode`, optionally preceded by a base value,
+    /// old & new value for `diff` or more `NAME VALUE` pairs
+    args: Vec<String>,
+
+    /// File with a `NAME VALUE` pair per line (`-` for stdin), errors are reported per line
+    #[clap(long)]
+    batch: Option<String>,
+
+    /// Address of a raw binary for `dis`
+    #[clap(long, value_parser=maybe_hex::<u64>, default_value_t = 0)]
+    base: u64,
+
+    /// Address to start disassembling at for `dis`
+    #[clap(long, value_parser=maybe_hex::<u64>, default_value_t = 0)]
+    start: u64,
+
+    /// Address to stop disassembling at for `dis`
+    #[clap(long, value_parser=maybe_hex::<u64>, default_value_t = u64::MAX)]
+    end: u64,
+
+    /// Register width of the hart, 32 or 64
+    #[clap(long, value_parser=parse_xlen, default_value_t = 64)]
+    xlen: usize,
+
+    /// Upper half of an RV32 CSR that is split across two registers, e.g. `mstatush` for `mstatus`
+    #[clap(long, value_parser=maybe_hex::<u64>)]
+    high: Option<u64>,
+
+    /// Output format of CSRs, virtual memory related formats, instructions & instruction layouts,
+    /// logs & disassembly are only printed as text
+    #[clap(long, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    /// When to style output & errors with colours, `auto` does so on terminals unless `NO_COLOR`
+    /// is set
+    #[clap(long, value_enum, default_value_t = Color::Auto)]
+    color: Color,
+
+    /// Custom instruction "NAME MATCH MASK [OPERAND...]" in custom-0..3 opcode space
+    #[clap(long)]
+    custom: Vec<String>,
 }
 
+/// Output formats
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    /// Human readable text
+    Text,
+    /// A JSON object per format
+    Json,
+}
+
+/// When to use colours
+#[derive(Clone, Copy, ValueEnum)]
+enum Color {
+    /// Only on terminals, unless `NO_COLOR` is set
+    Auto,
+    /// Always
+    Always,
+    /// Never
+    Never,
+}
+
+impl Color {
+    /// Pick a renderer for a stream that may be a terminal
+    fn render(self, terminal: bool) -> Render {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        match self {
+            Color::Always => Render::Ansi,
+            Color::Auto if terminal && !no_color => Render::Ansi,
+            _ => Render::Plain,
+        }
+    }
+}
+
+impl Cli {
+    /// Get the renderer for stdout
+    fn render(&self) -> Render {
+        self.color.render(io::stdout().is_terminal())
+    }
+
+    /// Get the value, exit if there is none
+    fn value(&self) -> &str {
+        self.value
+            .as_deref()
+            .unwrap_or_else(|| fail(&[&"Expected a VALUE"]))
+    }
+
+    /// Get the file to read for subcommands taking one, stdin by default
+    fn input(&self) -> &str {
+        self.value.as_deref().unwrap_or("-")
+    }
+}
+
+/// Renderer for errors on stderr
+static ERRORS: OnceLock<Render> = OnceLock::new();
+
 fn main() -> ! {
     let cli = Cli::parse();
+    ERRORS.get_or_init(|| cli.color.render(io::stderr().is_terminal()));
+    if let Err(e) = inst::set_custom(&cli.custom) {
+        fail(&[&e])
+    }
 
-    // Try to format as instruction
-    if let "ins" | "inst" | "instr" = cli.name.as_str() {
-        todo!("Format instructions")
-        // let inst = inst::format(cli.value);
-        // print!("{}", inst);
-        // exit(0)
+    // Try to format every pair in a batch
+    if let Some(path) = &cli.batch {
+        if cli.high.is_some() {
+            fail(&[&"--high only applies to a single NAME VALUE pair"])
+        }
+        match open_input(path).and_then(|input| format_batch(&cli, input)) {
+            Ok(ok) => exit(if ok { 0 } else { -1 }),
+            Err(e) => fail(&[&e]),
+        }
     }
+    let name = cli.name.clone().unwrap_or_default();
 
-    // Try to format as CSR
-    let csr = csr::format(&cli.name, cli.value);
-    if let Ok(csr) = csr {
-        print!("{}", csr);
+    // Logs & disassembly are only explained as text
+    if let (Output::Json, "dis" | "disasm" | "annotate" | "crash" | "trace") =
+        (cli.output, name.as_str())
+    {
+        fail(&[&format!("{} does not support --output json", name)])
+    }
+
+    // Try to assemble instruction
+    if let "asm" = name.as_str() {
+        match inst::assemble(cli.value()) {
+            Ok(inst) => {
+                let digits = inst::length(inst.value()) * 2;
+                match cli.output {
+                    Output::Text => println!("0x{:0digits$x}", inst.value()),
+                    Output::Json => println!("{}", json_inst(&inst)),
+                }
+                exit(0)
+            }
+            Err(e) => fail(&[&e]),
+        }
+    }
+
+    // Try to disassemble file
+    if let "dis" | "disasm" = name.as_str() {
+        match image::load(cli.input(), cli.base) {
+            Ok(image) => {
+                for (address, bytes) in image.range(cli.start, cli.end) {
+                    for line in inst::disassemble(bytes, address) {
+                        print!("{}", line);
+                    }
+                }
+                exit(0)
+            }
+            Err(e) => fail(&[&e]),
+        }
+    }
+
+    // Try to decode every CSR in a register dump
+    if let "regs" = name.as_str() {
+        let text = match read_input(cli.input()) {
+            Ok(text) => text,
+            Err(e) => fail(&[&e]),
+        };
+        for (name, value) in log::registers(&text) {
+            if let Ok((name, csr)) = csr::format(&name, value, cli.xlen) {
+                show(&cli, name, &*csr, value);
+                if let Output::Text = cli.output {
+                    println!();
+                }
+            }
+        }
         exit(0)
     }
 
-    // Try to format virtual memory related
-    let vmem = vmem::format(&cli.name, cli.value);
-    if let Ok(vmem) = vmem {
-        print!("{}", vmem);
+    // Try to annotate a log with the values it contains
+    if let "annotate" = name.as_str() {
+        let input = match open_input(cli.input()) {
+            Ok(input) => input,
+            Err(e) => fail(&[&e]),
+        };
+        for line in input.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => fail(&[&e]),
+            };
+            println!("{}", line);
+            for (name, value) in log::values(&line) {
+                if let Ok((name, csr)) = try_build(&name, value, cli.xlen) {
+                    for field in cli.render().csr(name, &*csr).lines() {
+                        println!("    {}", field);
+                    }
+                }
+            }
+        }
         exit(0)
     }
 
-    // Output error if formatting failed
-    eprint!("\x1b[31m\x1b[1m");
-    eprintln!("Formatting failed:");
-    eprintln!("\t- {}", csr.err().unwrap());
-    eprintln!("\t- {}", vmem.err().unwrap());
-    eprint!("\x1b[0m");
+    // Try to explain a crash dump
+    if let "crash" = name.as_str() {
+        let text = match read_input(cli.input()) {
+            Ok(text) => text,
+            Err(e) => fail(&[&e]),
+        };
+        let Some(crash) = log::crash(&text, cli.xlen) else {
+            fail(&[&"No Linux oops or OpenSBI trap dump found"])
+        };
+        println!("{}\n", crash.render(cli.render()));
+        for (name, csr) in crash.csrs {
+            println!("{}", cli.render().csr(name, &*csr));
+        }
+        exit(0)
+    }
+
+    // Try to build a CSR timeline of a simulation trace
+    if let "trace" = name.as_str() {
+        let text = match read_input(cli.input()) {
+            Ok(text) => text,
+            Err(e) => fail(&[&e]),
+        };
+        for event in log::timeline(&text, cli.xlen) {
+            print!("{}", event.render(cli.render()));
+        }
+        exit(0)
+    }
+
+    // Try to encode from field assignments
+    if let "enc" | "encode" = name.as_str() {
+        let (base, assignments) = match cli.args.split_first() {
+            Some((base, rest)) if maybe_hex::<u64>(base).is_ok() => {
+                (maybe_hex(base).unwrap(), rest)
+            }
+            _ => (0, &cli.args[..]),
+        };
+        match build(cli.value(), base, cli.xlen)
+            .1
+            .encode(base, assignments)
+        {
+            Ok(value) => {
+                match cli.output {
+                    Output::Text => println!("0x{:x}", value),
+                    Output::Json => {
+                        let (name, csr) = build(cli.value(), value, cli.xlen);
+                        show(&cli, name, &*csr, value)
+                    }
+                }
+                exit(0)
+            }
+            Err(e) => fail(&[&e]),
+        }
+    }
+
+    // Try to diff two values
+    if let "diff" = name.as_str() {
+        let values: Vec<u64> = match cli.args.iter().map(|v| maybe_hex(v)).collect() {
+            Ok(values) => values,
+            Err(e) => fail(&[&e]),
+        };
+        let [old, new] = values[..] else {
+            fail(&[&"diff takes an old and a new value"])
+        };
+        let changes = format::diff(
+            &*build(cli.value(), old, cli.xlen).1,
+            &*build(cli.value(), new, cli.xlen).1,
+        );
+        match cli.output {
+            Output::Text => {
+                let render = cli.render();
+                for (o, n) in changes {
+                    println!(
+                        "{}: {} \u{2192} {}",
+                        o.name,
+                        render.decoded(&o.decoded),
+                        render.decoded(&n.decoded)
+                    );
+                }
+            }
+            Output::Json => {
+                let changes: Vec<String> = changes
+                    .iter()
+                    .map(|(o, n)| {
+                        format!(
+                            "{{\"name\":{},\"old\":{},\"new\":{}}}",
+                            render::json_str(o.name),
+                            render::json_str(&o.decoded.to_string()),
+                            render::json_str(&n.decoded.to_string())
+                        )
+                    })
+                    .collect();
+                println!(
+                    "{{\"old\":\"0x{:x}\",\"new\":\"0x{:x}\",\"changes\":[{}]}}",
+                    old,
+                    new,
+                    changes.join(",")
+                );
+            }
+        }
+        exit(0)
+    }
+
+    // Try to format several pairs, reporting errors per pair
+    if !cli.args.is_empty() {
+        if cli.args.len() % 2 != 0 {
+            fail(&[&"Expected NAME VALUE pairs"])
+        }
+        if cli.high.is_some() {
+            fail(&[&"--high only applies to a single NAME VALUE pair"])
+        }
+        let mut ok = format_pair(&cli, &name, cli.value(), &failed(&name));
+        for pair in cli.args.chunks(2) {
+            ok &= format_pair(&cli, &pair[0], &pair[1], &failed(&pair[0]));
+        }
+        exit(if ok { 0 } else { -1 })
+    }
+
+    // Try to format a single pair
+    if let Err(errors) = format_value(&cli, &name, cli.value()) {
+        fail(&errors.iter().map(|e| e as &dyn Display).collect::<Vec<_>>())
+    }
+    exit(0)
+}
+
+/// Format and print the value of `name`, as an instruction, instruction layout, CSR or virtual
+/// memory related format
+///
+/// Returns why formatting failed
+fn format_value(cli: &Cli, name: &str, value: &str) -> Result<(), Vec<String>> {
+    let value = maybe_hex::<u64>(value).map_err(|e| vec![e])?;
+
+    // Try to format as instruction
+    if let "ins" | "inst" | "instr" = name {
+        let inst = inst::format(value).map_err(|e| vec![e.to_string()])?;
+        match cli.output {
+            Output::Text => print!("{}", inst),
+            Output::Json => println!("{}", json_inst(&inst)),
+        }
+        return Ok(());
+    }
+
+    // Try to format as instruction layout
+    if let "inst-fields" = name {
+        let layout = inst::layout(value).map_err(|e| vec![e.to_string()])?;
+        show(cli, layout.name(), &*layout, value);
+        return Ok(());
+    }
+
+    // Try to format as both halves of an RV32 CSR
+    if let Some(high) = cli.high {
+        let (name, csr) = csr::format_pair(name, value, high).map_err(|e| vec![e.to_string()])?;
+        show(cli, name, &*csr, high << 32 | value);
+        return Ok(());
+    }
+
+    // Try to format as CSR or virtual memory related
+    let (name, csr) = try_build(name, value, cli.xlen)
+        .map_err(|(csr, vmem)| vec![csr.to_string(), vmem.to_string()])?;
+    show(cli, name, &*csr, value);
+    Ok(())
+}
+
+/// A format & the name to show it by
+type Named = (&'static str, Box<dyn format::Csr>);
+
+/// Build a CSR or virtual memory related format & the name to show it by from its name & value,
+/// exit if there is none
+fn build(name: &str, value: u64, xlen: usize) -> Named {
+    try_build(name, value, xlen).unwrap_or_else(|(csr, vmem)| fail(&[&csr, &vmem]))
+}
+
+/// Build a CSR or virtual memory related format & the name to show it by from its name & value
+fn try_build(
+    name: &str,
+    value: u64,
+    xlen: usize,
+) -> Result<Named, (csr::CsrError, vmem::VmemError)> {
+    let csr = csr::format(name, value, xlen);
+    if let Ok(csr) = csr {
+        return Ok(csr);
+    }
+    let vmem = vmem::format(name, value);
+    if let Ok(vmem) = vmem {
+        return Ok((vmem.name(), vmem));
+    }
+    Err((csr.err().unwrap(), vmem.err().unwrap()))
+}
+
+/// Format and print a `NAME VALUE` pair per line, report errors per line
+///
+/// Returns whether every line was formatted
+fn format_batch(cli: &Cli, input: impl BufRead) -> io::Result<bool> {
+    let mut ok = true;
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let heading = format!("Formatting line {} failed", i + 1);
+        match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => {}
+            [comment, ..] if comment.starts_with('#') => {}
+            [name, value] => ok &= format_pair(cli, name, value, &heading),
+            _ => {
+                report(&heading, &[&"Expected a NAME VALUE pair"]);
+                ok = false;
+            }
+        }
+    }
+    Ok(ok)
+}
+
+/// Format and print a `NAME VALUE` pair, report why under `heading` if that fails
+///
+/// Returns whether formatting succeeded
+fn format_pair(cli: &Cli, name: &str, value: &str, heading: &str) -> bool {
+    match format_value(cli, name, value) {
+        Ok(()) => {
+            if let Output::Text = cli.output {
+                println!();
+            }
+            true
+        }
+        Err(errors) => {
+            report(
+                heading,
+                &errors.iter().map(|e| e as &dyn Display).collect::<Vec<_>>(),
+            );
+            false
+        }
+    }
+}
+
+/// Print a format under `name` as text or JSON
+fn show(cli: &Cli, name: &str, csr: &dyn format::Csr, value: u64) {
+    match cli.output {
+        Output::Text => print!("{}", cli.render().csr(name, csr)),
+        Output::Json => println!("{}", render::json(name, csr, value)),
+    }
+}
+
+/// Render an instruction as a JSON object holding its value, text & 32 bit equivalent
+fn json_inst(inst: &inst::Inst) -> String {
+    let expansion = match inst.expand() {
+        Some(e) => render::json_str(&e.text()),
+        None => "null".into(),
+    };
+    format!(
+        "{{\"value\":\"0x{:x}\",\"text\":{},\"expansion\":{}}}",
+        inst.value(),
+        render::json_str(&inst.text()),
+        expansion
+    )
+}
+
+/// Parse a supported XLEN
+fn parse_xlen(s: &str) -> Result<usize, String> {
+    match s {
+        "32" => Ok(32),
+        "64" => Ok(64),
+        _ => Err(format!("{} is not a supported XLEN, use 32 or 64", s)),
+    }
+}
+
+/// Heading of the errors of a pair that failed
+fn failed(name: &str) -> String {
+    format!("Formatting {} failed", name)
+}
+
+/// Open a file, or stdin if `path` is `-`
+fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    match path {
+        "-" => Ok(Box::new(io::stdin().lock())),
+        _ => Ok(Box::new(BufReader::new(File::open(path)?))),
+    }
+}
+
+/// Read a whole file, or stdin if `path` is `-`
+fn read_input(path: &str) -> io::Result<String> {
+    io::read_to_string(open_input(path)?)
+}
+
+/// Print the errors that caused formatting to fail and exit
+fn fail(errors: &[&dyn Display]) -> ! {
+    report("Formatting failed", errors);
     exit(-1)
 }
+
+/// Print errors under a heading
+fn report(heading: &str, errors: &[&dyn Display]) {
+    let mut s = format!("{}:\n", heading);
+    for e in errors {
+        s += &format!("\t- {}\n", e);
+    }
+    let render = ERRORS.get().copied().unwrap_or(Render::Plain);
+    eprint!("{}", render.error(&s));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batch_reports_malformed_addresses() {
+        let cli = Cli::parse_from(["rv-format", "--batch", "-"]);
+        let input = "mstatus 0x8\n0xzz 5\n0x10000 5\n# comment\nsatp 0\n";
+        assert!(!format_batch(&cli, input.as_bytes()).unwrap());
+        assert!(format_batch(&cli, "mstatus 0x8\n\nsatp 0\n".as_bytes()).unwrap());
+    }
+}
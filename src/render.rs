@@ -0,0 +1,211 @@
+//! Rendering of formats as plain text, ANSI styled text or JSON
+
+use core::fmt::{self, Display};
+
+#[cfg(feature = "std")]
+use crate::bitfield::Decoded;
+use crate::bitfield::{Style, StyledWrite};
+use crate::format::Csr;
+
+/// Renders formats as text
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Render {
+    /// Text without any styling
+    Plain,
+    /// Text styled with ANSI escape sequences
+    Ansi,
+}
+
+impl Render {
+    /// Write a format as its name, underlined, followed by a `field: value` line per field
+    pub fn write_csr(self, csr: &dyn Csr, out: &mut dyn fmt::Write) -> fmt::Result {
+        self.write_named(csr.name(), csr, out)
+    }
+
+    /// Write a format like [Render::write_csr] under another name, e.g. the CSR it was read from
+    pub fn write_named(self, name: &str, csr: &dyn Csr, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(out, "{}\n{:-<2$}", name, "", name.len())?;
+        let values = (0..).map_while(|i| csr.field(i));
+        for (field, value) in csr.layout().iter().zip(values) {
+            write!(out, "{}: ", field.name)?;
+            value.write(&mut Styled(self, &mut *out))?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Render a decoded value
+    #[cfg(feature = "std")]
+    pub fn decoded(self, decoded: &Decoded) -> String {
+        let mut s = String::new();
+        let mut out = Styled(self, &mut s);
+        for span in &decoded.spans {
+            out.write_styled(span.style, &span.text)
+                .expect("Rendering into memory should not fail");
+        }
+        s
+    }
+
+    /// Render an error message
+    #[cfg(feature = "std")]
+    pub fn error(self, text: &str) -> String {
+        match self {
+            Render::Plain => text.to_string(),
+            Render::Ansi => format!("\x1b[31m\x1b[1m{}\x1b[0m", text),
+        }
+    }
+
+    /// Render a format as the given name, underlined, followed by a `field: value` line per field
+    #[cfg(feature = "std")]
+    pub fn csr(self, name: &str, csr: &dyn Csr) -> String {
+        let mut s = String::new();
+        self.write_named(name, csr, &mut s)
+            .expect("Rendering into memory should not fail");
+        s
+    }
+}
+
+/// Writes styled text into a [fmt::Write] the way a [Render] styles it
+struct Styled<'a, W: ?Sized>(Render, &'a mut W);
+
+impl<W: fmt::Write + ?Sized> StyledWrite for Styled<'_, W> {
+    fn write_styled(&mut self, style: Style, text: &dyn Display) -> fmt::Result {
+        match (self.0, style) {
+            (Render::Plain, _) | (Render::Ansi, Style::Normal) => write!(self.1, "{}", text),
+            (Render::Ansi, Style::Warning) => write!(self.1, "\x1b[33m{}\x1b[0m", text),
+            (Render::Ansi, Style::Dim) => write!(self.1, "\x1b[90m{}\x1b[0m", text),
+        }
+    }
+}
+
+/// Render a format as a JSON object holding the given name, its raw value and fields
+///
+/// _Raw values are written as `"0x.."` strings since JSON numbers above 2^53 lose precision in
+/// many parsers_
+///
+/// _A field's `warning` holds the text of the decoded value's warnings, if it contains any_
+#[cfg(feature = "std")]
+pub fn json(name: &str, csr: &dyn Csr, value: u64) -> String {
+    let fields: Vec<String> = csr
+        .fields()
+        .map(|f| {
+            let bits: Vec<String> = f
+                .bits
+                .iter()
+                .map(|(s, e)| format!("[{},{}]", s, e))
+                .collect();
+            let text = json_str(&f.decoded.to_string());
+            let warning = match f.decoded.is_warning() {
+                true => json_str(&f.decoded.warnings()),
+                false => "null".into(),
+            };
+            format!(
+                "{{\"name\":{},\"bits\":[{}],\"value\":\"0x{:x}\",\"text\":{},\"warning\":{}}}",
+                json_str(f.name),
+                bits.join(","),
+                f.value,
+                text,
+                warning
+            )
+        })
+        .collect();
+    format!(
+        "{{\"name\":{},\"value\":\"0x{:x}\",\"fields\":[{}]}}",
+        json_str(name),
+        value,
+        fields.join(",")
+    )
+}
+
+/// Quote & escape a string for JSON
+#[cfg(feature = "std")]
+pub fn json_str(s: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::csr::{PmpCfg, Satp};
+
+    #[test]
+    fn render_styles() {
+        let pmpcfg = PmpCfg::new(0x12);
+        let field = pmpcfg.fields().next().unwrap();
+        assert!(field.decoded.is_warning());
+        assert_eq!(
+            Render::Plain.decoded(&field.decoded),
+            "Illegal PMP permissions: -W- NA4 "
+        );
+        assert_eq!(
+            Render::Ansi.decoded(&field.decoded),
+            "\x1b[33mIllegal PMP permissions: \x1b[0m\x1b[90m-\x1b[0mW\x1b[90m-\x1b[0m NA4 "
+        );
+        assert_eq!(
+            Render::Plain.csr("satp", &Satp::new(0)).lines().nth(2),
+            Some("mode: Bare")
+        );
+    }
+
+    #[test]
+    fn json_warnings() {
+        let json = json("pmpcfg0", &PmpCfg::new(0x12), 0x12);
+        assert!(json.contains("\"text\":\"Illegal PMP permissions: -W- NA4 \""));
+        assert!(json.contains("\"warning\":\"Illegal PMP permissions: \""));
+        assert!(json.contains("\"warning\":null"));
+    }
+
+    #[test]
+    fn write_csr_without_allocating() {
+        struct Buf([u8; 64], usize);
+        impl fmt::Write for Buf {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let end = self.1 + s.len();
+                self.0
+                    .get_mut(self.1..end)
+                    .ok_or(fmt::Error)?
+                    .copy_from_slice(s.as_bytes());
+                self.1 = end;
+                Ok(())
+            }
+        }
+        let mut buf = Buf([0; 64], 0);
+        Render::Plain
+            .write_csr(&Satp::new(0x8000000000000123), &mut buf)
+            .unwrap();
+        assert_eq!(
+            &buf.0[..buf.1],
+            Render::Plain
+                .csr("satp", &Satp::new(0x8000000000000123))
+                .as_bytes()
+        );
+        assert!(Render::Plain
+            .write_csr(&PmpCfg::new(0x1f), &mut Buf([0; 64], 0))
+            .is_err());
+    }
+
+    #[test]
+    fn json_fields() {
+        let satp = Satp::new(0xf000000000000000);
+        assert_eq!(
+            json("vsatp", &satp, 0xf000000000000000),
+            "{\"name\":\"vsatp\",\"value\":\"0xf000000000000000\",\"fields\":[\
+             {\"name\":\"mode\",\"bits\":[[60,63]],\"value\":\"0xf\",\
+             \"text\":\"Invalid address translation mode (0b1111)\",\
+             \"warning\":\"Invalid address translation mode (0b1111)\"},\
+             {\"name\":\"asid\",\"bits\":[[44,59]],\"value\":\"0x0\",\"text\":\"0x0\",\"warning\":null},\
+             {\"name\":\"ppn\",\"bits\":[[0,43]],\"value\":\"0x0\",\"text\":\"0x0 -> 0x0\",\"warning\":null}]}"
+        );
+        assert_eq!(json_str("a\"b\\\n"), "\"a\\\"b\\\\\\u000a\"");
+    }
+}
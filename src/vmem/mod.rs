@@ -2,9 +2,11 @@
 //!
 //! *Note that these are not CSRs but rather (double)words in memory*
 
+#[cfg(feature = "std")]
 use thiserror::Error;
 
-use crate::{csr, format::Csr};
+#[cfg(feature = "std")]
+use crate::format::Csr;
 
 // Export vmem formats
 mod va;
@@ -15,6 +17,7 @@ mod pte;
 pub use pte::*;
 
 /// Errors that may arise when creating/handling a CSR
+#[cfg(feature = "std")]
 #[derive(Error, Debug)]
 pub enum VmemError {
     #[error("\"{0}\" is not a name of a supported virtual memory related format")]
@@ -22,6 +25,7 @@ pub enum VmemError {
 }
 
 /// Build a vmem [BitFieldStruct] from its name & value
+#[cfg(feature = "std")]
 pub fn format(vmem_str: &str, value: u64) -> Result<Box<dyn Csr>, VmemError> {
     match vmem_str {
         "pte_sv32" | "sv32_pte" => Ok(Box::new(Pte32::new(value))),
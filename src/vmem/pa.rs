@@ -1,7 +1,7 @@
 //! Physical address formats
 
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
 use crate::bitfield::{BitField, Hex, Ppn};
 use crate::format::Csr;
@@ -1,7 +1,7 @@
 //! Page table entry formats
 
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
 use crate::bitfield::{Bin, BitField, Ppn};
 use crate::format::Csr;
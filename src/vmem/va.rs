@@ -1,9 +1,9 @@
 //! virtual address formats
 
+use core::fmt::Display;
 use csr_macro::Csr;
-use std::fmt::Display;
 
-use crate::bitfield::{BitField, Hex, RSh};
+use crate::bitfield::{BitField, Hex, RSh, SExt};
 use crate::format::Csr;
 
 /// Sv32 Virtual Address
@@ -17,6 +17,7 @@ pub struct VAddr32 {
 /// Sv39 Virtual Address
 #[derive(Csr)]
 pub struct VAddr39 {
+    vaddr: BitField<SExt, 0, 38>,
     page_offset: BitField<Hex, 0, 11>,
     vpn0: BitField<RSh<3, Hex>, 12, 20>,
     vpn1: BitField<RSh<3, Hex>, 21, 29>,
@@ -26,6 +27,7 @@ pub struct VAddr39 {
 /// Sv48 Virtual Address
 #[derive(Csr)]
 pub struct VAddr48 {
+    vaddr: BitField<SExt, 0, 47>,
     page_offset: BitField<Hex, 0, 11>,
     vpn0: BitField<RSh<3, Hex>, 12, 20>,
     vpn1: BitField<RSh<3, Hex>, 21, 29>,
@@ -36,6 +38,7 @@ pub struct VAddr48 {
 /// Sv57 Virtual Address
 #[derive(Csr)]
 pub struct VAddr57 {
+    vaddr: BitField<SExt, 0, 56>,
     page_offset: BitField<Hex, 0, 11>,
     vpn0: BitField<RSh<3, Hex>, 12, 20>,
     vpn1: BitField<RSh<3, Hex>, 21, 29>,